@@ -0,0 +1,50 @@
+//! `/dic` の辞書をスプレッドシートとローカルの JSON ファイルの間で書き写す
+//!
+//! ```text
+//! cargo run --bin dic_sync -- export <スプレッドシート ID> <JSON ファイル>
+//! cargo run --bin dic_sync -- import <JSON ファイル> <スプレッドシート ID>
+//! ```
+//!
+//! 認証には `GOOGLE_SERVICE_ACCOUNT_KEY_JSON` か `GOOGLE_APPLICATION_CREDENTIALS` 環境変数の
+//! 認証情報を使う（どちらもなければメタデータサーバー）。
+//! `import` はシートのヘッダーより下の行をすべて書き換えるので、シートの編集権限が必要。
+
+use nidaime_takohachi::google_auth;
+use nidaime_takohachi::google_sheets::{SCOPE_READ_WRITE, SheetsClient};
+use nidaime_takohachi::trigger::{self, LocalTriggerStore, TriggerSheet};
+
+fn usage() -> ! {
+    eprintln!("Usage:");
+    eprintln!("  dic_sync export <spreadsheet_id> <json_file>");
+    eprintln!("  dic_sync import <json_file> <spreadsheet_id>");
+    std::process::exit(2);
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let credentials = google_auth::default_provider(None);
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["export", spreadsheet_id, json_path] => {
+            let sheet = TriggerSheet::new(SheetsClient::new(credentials, spreadsheet_id));
+            let local = LocalTriggerStore::new(json_path);
+            trigger::sync(&sheet, &local).await
+        }
+        ["import", json_path, spreadsheet_id] => {
+            let local = LocalTriggerStore::new(json_path);
+            let client =
+                SheetsClient::new(credentials, spreadsheet_id).with_scope(SCOPE_READ_WRITE);
+            let sheet = TriggerSheet::new(client);
+            trigger::sync(&local, &sheet).await
+        }
+        _ => usage(),
+    };
+
+    match result {
+        Ok(count) => println!("Copied {} entries.", count),
+        Err(e) => {
+            eprintln!("Failed to sync the dictionary: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
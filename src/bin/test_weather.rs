@@ -0,0 +1,39 @@
+use nidaime_takohachi::weather::{jma, render};
+use std::env;
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    // デフォルトは東京都
+    let office_code = if args.len() > 1 { &args[1] } else { "130000" };
+
+    println!("週間天気予報を取得中: {}", office_code);
+    println!("----------------------------------------");
+    match jma::fetch_weekly_forecast(office_code).await {
+        Ok(forecast) => println!("{}", render::weekly(&forecast)),
+        Err(e) => println!("❌ エラーが発生しました: {}", e),
+    }
+
+    println!();
+    println!("警報・注意報を取得中: {}", office_code);
+    println!("----------------------------------------");
+    match jma::fetch_warnings(office_code).await {
+        Ok(warnings) => {
+            println!("{}", warnings.headline);
+            for area in &warnings.areas {
+                let names: Vec<String> = area
+                    .active()
+                    .map(|w| format!("{}（{}）", w.name(), w.status))
+                    .collect();
+                let names = if names.is_empty() {
+                    "なし".to_string()
+                } else {
+                    names.join("、")
+                };
+                println!("- {}: {}", area.code, names);
+            }
+        }
+        Err(e) => println!("❌ エラーが発生しました: {}", e),
+    }
+}
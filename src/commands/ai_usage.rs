@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serenity::builder::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use super::{CommandError, SlashCommand};
+use crate::config::Config;
+use nidaime_takohachi::rate_limit::RateLimiter;
+
+/// 一覧に表示するユーザー数
+const TOP_USERS: usize = 20;
+
+pub struct AiUsage {
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl AiUsage {
+    pub fn new(rate_limiter: Arc<RateLimiter>) -> Self {
+        Self { rate_limiter }
+    }
+}
+
+#[async_trait]
+impl SlashCommand for AiUsage {
+    fn name(&self) -> &'static str {
+        "ai_usage"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("AI コマンドの今日の利用状況を表示します（管理者用）")
+            .default_member_permissions(Permissions::MANAGE_GUILD)
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.gemini.is_some()
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let usage = self.rate_limiter.usage();
+        let settings = self.rate_limiter.settings();
+
+        let format_limit = |limit: Option<u32>| {
+            limit
+                .map(|l| format!("{}回", l))
+                .unwrap_or_else(|| "なし".to_string())
+        };
+
+        let users = if usage.users.is_empty() {
+            "まだ誰も使っていません。".to_string()
+        } else {
+            usage
+                .users
+                .iter()
+                .take(TOP_USERS)
+                .map(|(user_id, count)| format!("- <@{}>: {}回", user_id, count))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let embed = CreateEmbed::new()
+            .title(format!("📊 AI コマンドの利用状況 ({})", usage.date))
+            .colour(Colour::DARK_GREEN)
+            .field(
+                "サーバー全体",
+                format!(
+                    "{}回 / 上限 {}",
+                    usage.total,
+                    format_limit(settings.guild_daily_limit)
+                ),
+                false,
+            )
+            .field(
+                "制限",
+                format!(
+                    "クールダウン: {}秒\nユーザーごとの上限: {}",
+                    settings.cooldown.as_secs(),
+                    format_limit(settings.user_daily_limit)
+                ),
+                false,
+            )
+            .field("ユーザー別", users, false)
+            .footer(CreateEmbedFooter::new(
+                "回数は日本時間の 0 時と Bot の再起動でリセットされます",
+            ));
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .embed(embed)
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+}
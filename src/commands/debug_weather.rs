@@ -1,46 +1,68 @@
+use async_trait::async_trait;
+use nidaime_takohachi::weather;
 use serenity::builder::{CreateCommand, CreateCommandOption};
 use serenity::model::application::{CommandInteraction, CommandOptionType};
 use serenity::prelude::*;
 
-pub async fn run(
-    ctx: &Context,
-    interaction: &CommandInteraction,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let citycode = interaction
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "citycode")
-        .and_then(|opt| opt.value.as_str())
-        .unwrap_or("130010");
-
-    let response_content = match nidaime_takohachi::utils::get_weather(citycode).await {
-        Ok(weather_info) => format!("🌤️ **デバッグ: 天気情報取得テスト**\n\n{}", weather_info),
-        Err(e) => format!("❌ 天気情報の取得に失敗しました: {}", e),
-    };
-
-    interaction
-        .create_response(
-            &ctx.http,
-            serenity::builder::CreateInteractionResponse::Message(
-                serenity::builder::CreateInteractionResponseMessage::new()
-                    .content(response_content),
-            ),
-        )
-        .await?;
+use super::{CommandError, SlashCommand};
+use crate::config::Config;
 
-    Ok(())
-}
+pub struct DebugWeather;
+
+#[async_trait]
+impl SlashCommand for DebugWeather {
+    fn name(&self) -> &'static str {
+        "debug_weather"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("天気情報取得機能のテスト（デバッグ用）")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "citycode",
+                    "都市コード (例: 130010=東京, 060010=山形)",
+                )
+                .required(false),
+            )
+    }
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("debug_weather")
-        .description("天気情報取得機能のテスト（デバッグ用）")
-        .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::String,
-                "citycode",
-                "都市コード (例: 130010=東京, 060010=山形)",
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.debug_slash_commands
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let citycode = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "citycode")
+            .and_then(|opt| opt.value.as_str())
+            .unwrap_or("130010");
+
+        let response_content = match weather::fetch_forecast(citycode).await {
+            Ok(forecast) => format!(
+                "🌤️ **デバッグ: 天気情報取得テスト**\n\n{}",
+                weather::render::details(&forecast)
+            ),
+            Err(e) => format!("❌ 天気情報の取得に失敗しました: {}", e),
+        };
+
+        interaction
+            .create_response(
+                &ctx.http,
+                serenity::builder::CreateInteractionResponse::Message(
+                    serenity::builder::CreateInteractionResponseMessage::new()
+                        .content(response_content),
+                ),
             )
-            .required(false),
-        )
+            .await?;
+
+        Ok(())
+    }
 }
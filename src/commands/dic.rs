@@ -1,97 +1,211 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use rand::seq::SliceRandom;
 use serenity::builder::*;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
+use serenity::utils::CreateQuickModal;
 
-use crate::config::Config;
+use super::error::report_modal_error;
+use super::gemini_preset::AUTOCOMPLETE_LIMIT;
+use super::paginator::{Paginator, truncate};
+use super::{CommandError, SlashCommand, respond, string_option};
+use crate::config::{Config, DicConfig};
+use nidaime_takohachi::normalize::normalize_keyword;
+use nidaime_takohachi::trigger::{HitCounter, TriggerEntry, TriggerFields, TriggerStore};
 
-pub async fn run(ctx: &Context, interaction: &CommandInteraction) -> Result<(), serenity::Error> {
-    interaction.defer(&ctx.http).await?;
+/// 入力補完の候補の名前の上限文字数
+const CHOICE_NAME_LIMIT: usize = 100;
+/// 「もしかして」で提示するキーワードの数（ボタン 1 行に収まる数）
+const SUGGESTION_LIMIT: usize = 5;
+const SUGGESTION_BUTTON_PREFIX: &str = "dic:suggest:";
+const SELECT_MENU_ID: &str = "dic:select";
+/// セレクトメニューに並べられる選択肢の上限
+const SELECT_OPTION_LIMIT: usize = 25;
+/// セレクトメニューの選択肢の名前・説明の上限文字数
+const SELECT_TEXT_LIMIT: usize = 100;
+/// 候補が選ばれるのを待つ時間
+const CHOICE_TIMEOUT: Duration = Duration::from_secs(60);
+const BUTTON_LABEL_LIMIT: usize = 80;
+/// 追加・編集のモーダルの入力を待つ時間
+const MODAL_TIMEOUT: Duration = Duration::from_secs(600);
+/// `/dic list` の 1 ページに並べる項目の数
+const LIST_PAGE_SIZE: usize = 20;
+/// `/dic top` に並べる項目の数
+const TOP_LIMIT: usize = 10;
+const DEFAULT_HITS_PATH: &str = "dic_hits.json";
+/// 同じチャンネルで同じ項目に自動返信するまでに空ける既定の時間
+const DEFAULT_AUTO_RESPONSE_COOLDOWN: Duration = Duration::from_secs(5 * 60);
 
-    let keyword = interaction
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "keyword")
-        .and_then(|opt| opt.value.as_str())
-        .unwrap_or("");
-
-    // Load config in a block so the non-Send error is dropped before any await
-    let config = {
-        match Config::load() {
-            Ok(c) => Some(c),
-            Err(e) => {
-                println!("Failed to load config: {}", e);
-                None
-            }
+pub struct Dic {
+    config: Option<DicConfig>,
+    store: Option<Arc<dyn TriggerStore>>,
+    /// 項目ごとの表示回数（`/dic top` と、入力補完で人気のものを上に出すために使う）
+    hits: HitCounter,
+    /// チャンネルと項目ごとの最後に自動返信した時刻
+    auto_responded_at: Mutex<HashMap<(ChannelId, String), Instant>>,
+}
+
+impl Dic {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            store: config.dic.as_ref().map(DicConfig::trigger_store),
+            config: config.dic.clone(),
+            hits: load_hits(config),
+            auto_responded_at: Mutex::new(HashMap::new()),
         }
-    };
-    let Some(config) = config else {
-        interaction
-            .edit_response(
-                &ctx.http,
-                EditInteractionResponse::new()
-                    .content(":warning: データの取得中にエラーが発生しました。"),
-            )
-            .await?;
-        return Ok(());
-    };
+    }
+
+    /// 自動返信してよければ時刻を記録して `true` を返す（クールダウン中なら `false`）
+    fn start_auto_response(
+        &self,
+        channel_id: ChannelId,
+        entry: &TriggerEntry,
+        cooldown: Duration,
+    ) -> bool {
+        let mut responded_at = self.auto_responded_at.lock().unwrap();
+        responded_at.retain(|_, at| at.elapsed() < cooldown);
+        let key = (channel_id, entry.trigger().to_string());
+        if responded_at.contains_key(&key) {
+            return false;
+        }
+        responded_at.insert(key, Instant::now());
+        true
+    }
+
+    async fn record_hit(&self, entry: &TriggerEntry) {
+        if let Err(why) = self.hits.record(entry.trigger()).await {
+            eprintln!("[/dic] Failed to save hit count: {}", why);
+        }
+    }
+
+    /// 入力中の文字列に一致するキーワードを候補として返す
+    ///
+    /// 前方一致を部分一致より優先し、同じ順位の中ではよく使われるものを上に出す。
+    fn suggest(&self, entries: &[TriggerEntry], query: &str) -> Vec<AutocompleteChoice> {
+        let query = normalize_keyword(query);
+
+        let mut candidates: Vec<(bool, u32, &str, &str)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let trigger = entry.keywords.first()?;
+                // 前方一致するキーワードがあればそれを、なければ部分一致するものを使う
+                let keyword = entry
+                    .keywords
+                    .iter()
+                    .find(|k| normalize_keyword(k).starts_with(&query))
+                    .map(|k| (true, k))
+                    .or_else(|| {
+                        entry
+                            .keywords
+                            .iter()
+                            .find(|k| normalize_keyword(k).contains(&query))
+                            .map(|k| (false, k))
+                    });
+                let (prefix, keyword) = keyword?;
+                let count = self.hits.get(trigger);
+                Some((prefix, count, keyword.as_str(), trigger.as_str()))
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(b.1.cmp(&a.1))
+                .then(a.2.chars().count().cmp(&b.2.chars().count()))
+        });
+
+        candidates
+            .into_iter()
+            .take(AUTOCOMPLETE_LIMIT)
+            .map(|(_, _, keyword, trigger)| {
+                let name = if keyword == trigger {
+                    keyword.to_string()
+                } else {
+                    format!("{} (→ {})", keyword, trigger)
+                };
+                AutocompleteChoice::new(truncate(&name, CHOICE_NAME_LIMIT), keyword)
+            })
+            .collect()
+    }
+
+    async fn search(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        dic_config: &DicConfig,
+        store: &dyn TriggerStore,
+        options: &[ResolvedOption<'_>],
+    ) -> Result<(), CommandError> {
+        interaction.defer(&ctx.http).await?;
 
-    let dic_config = match &config.dic {
-        Some(c) => c,
-        None => {
+        let keyword = string_option(options, "keyword");
+
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+
+        let matches = table.find_all(keyword);
+        if let [entry] = matches.as_slice() {
+            self.record_hit(entry).await;
+            let reply = EntryReply::new(entry, keyword, dic_config);
             interaction
+                .edit_response(&ctx.http, reply.edit_response())
+                .await?;
+            return Ok(());
+        }
+
+        // 複数のエントリが一致した場合はセレクトメニューで選んでもらう
+        if !matches.is_empty() {
+            let options = matches
+                .iter()
+                .take(SELECT_OPTION_LIMIT)
+                .enumerate()
+                .map(|(i, entry)| {
+                    let mut option = CreateSelectMenuOption::new(
+                        truncate(entry.trigger(), SELECT_TEXT_LIMIT),
+                        i.to_string(),
+                    );
+                    let description = if entry.title.is_empty() {
+                        entry.tags.join(", ")
+                    } else {
+                        entry.title.clone()
+                    };
+                    if !description.is_empty() {
+                        option = option.description(truncate(&description, SELECT_TEXT_LIMIT));
+                    }
+                    option
+                })
+                .collect();
+            let menu =
+                CreateSelectMenu::new(SELECT_MENU_ID, CreateSelectMenuKind::String { options })
+                    .placeholder("表示する項目を選んでください");
+            let message = interaction
                 .edit_response(
                     &ctx.http,
                     EditInteractionResponse::new()
-                        .content(":warning: dic コマンドが設定されていません。"),
+                        .content(format!(
+                            ":mag: 「{}」に一致する項目が{}件あります。",
+                            keyword,
+                            matches.len()
+                        ))
+                        .components(vec![CreateActionRow::SelectMenu(menu)]),
                 )
                 .await?;
-            return Ok(());
-        }
-    };
 
-    let result = nidaime_takohachi::google_sheets::search_trigger(
-        &dic_config.service_account_key_path,
-        &dic_config.spreadsheet_id,
-        keyword,
-    )
-    .await;
-
-    match result {
-        Ok(Some(entry)) => {
-            if !entry.response.is_empty() {
-                interaction
-                    .edit_response(
-                        &ctx.http,
-                        EditInteractionResponse::new().content(&entry.response),
-                    )
-                    .await?;
-            } else {
-                let mut embed = CreateEmbed::new().colour(Colour::DARK_BLUE);
-
-                if !entry.title.is_empty() {
-                    embed = embed.title(&entry.title);
-                }
-                if !entry.description.is_empty() {
-                    embed = embed.description(format!(
-                        "{}\n\n[Check DB]({})",
-                        entry.description, dic_config.db_spreadsheet_url
-                    ));
-                }
-                if !entry.thumbnail_url.is_empty() {
-                    embed = embed.thumbnail(&entry.thumbnail_url);
-                }
-                if !entry.image_url.is_empty() {
-                    embed = embed.image(&entry.image_url);
-                }
-                embed = embed.footer(CreateEmbedFooter::new(format!("Keyword: {}", keyword)));
-
-                interaction
-                    .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
-                    .await?;
-            }
+            let choices: Vec<(&TriggerEntry, &str)> = matches
+                .iter()
+                .map(|entry| (*entry, entry.trigger()))
+                .collect();
+            return self
+                .wait_for_choice(ctx, interaction, &message, &choices, dic_config)
+                .await;
         }
-        Ok(None) => {
+
+        let suggestions = table.suggest(keyword, SUGGESTION_LIMIT);
+        if suggestions.is_empty() {
             interaction
                 .edit_response(
                     &ctx.http,
@@ -99,31 +213,820 @@ pub async fn run(ctx: &Context, interaction: &CommandInteraction) -> Result<(),
                         .content(format!(":warning: 「{}」は登録されていません。", keyword)),
                 )
                 .await?;
+            return Ok(());
         }
-        Err(e) => {
-            println!("Error searching trigger: {}", e);
-            interaction
-                .edit_response(
+
+        // 近いキーワードをボタンで提示し、押されたものを表示する
+        let buttons = suggestions
+            .iter()
+            .enumerate()
+            .map(|(i, suggestion)| {
+                CreateButton::new(format!("{}{}", SUGGESTION_BUTTON_PREFIX, i))
+                    .label(truncate(suggestion.keyword, BUTTON_LABEL_LIMIT))
+                    .style(ButtonStyle::Secondary)
+            })
+            .collect();
+        let message = interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(format!(
+                        ":thinking: 「{}」は登録されていません。もしかして…？",
+                        keyword
+                    ))
+                    .components(vec![CreateActionRow::Buttons(buttons)]),
+            )
+            .await?;
+
+        let choices: Vec<(&TriggerEntry, &str)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.entry, suggestion.keyword))
+            .collect();
+        self.wait_for_choice(ctx, interaction, &message, &choices, dic_config)
+            .await
+    }
+
+    /// 候補のボタンかセレクトメニューで選ばれたエントリを表示する
+    ///
+    /// 一定時間選ばれなければボタンやメニューを消して終了する。
+    async fn wait_for_choice(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        message: &Message,
+        choices: &[(&TriggerEntry, &str)],
+        dic_config: &DicConfig,
+    ) -> Result<(), CommandError> {
+        // 選べるのはコマンドを実行した人だけ
+        while let Some(press) = message
+            .await_component_interaction(&ctx.shard)
+            .author_id(interaction.user.id)
+            .timeout(CHOICE_TIMEOUT)
+            .await
+        {
+            let index = match &press.data.kind {
+                ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+                ComponentInteractionDataKind::Button => press
+                    .data
+                    .custom_id
+                    .strip_prefix(SUGGESTION_BUTTON_PREFIX)
+                    .map(str::to_string),
+                _ => None,
+            };
+            let Some(&(entry, keyword)) = index
+                .and_then(|i| i.parse::<usize>().ok())
+                .and_then(|i| choices.get(i))
+            else {
+                continue;
+            };
+
+            self.record_hit(entry).await;
+            let reply = EntryReply::new(entry, keyword, dic_config);
+            press
+                .create_response(
                     &ctx.http,
-                    EditInteractionResponse::new()
-                        .content(":warning: データの取得中にエラーが発生しました。"),
+                    CreateInteractionResponse::UpdateMessage(reply.update_message()),
                 )
                 .await?;
+            return Ok(());
+        }
+
+        interaction
+            .edit_response(&ctx.http, EditInteractionResponse::new().components(vec![]))
+            .await?;
+        Ok(())
+    }
+
+    /// 追加・編集ができるメンバーか（サーバー管理権限か `editor_role_ids` のロールを持つ）
+    fn can_edit(interaction: &CommandInteraction, dic_config: &DicConfig) -> bool {
+        let Some(member) = &interaction.member else {
+            return false;
+        };
+        member
+            .permissions
+            .is_some_and(|permissions| permissions.manage_guild())
+            || member
+                .roles
+                .iter()
+                .any(|role| dic_config.editor_role_ids.contains(&role.get()))
+    }
+
+    /// モーダルで入力を受け付け、新しいエントリを追加するか `current` を書き換える
+    async fn open_editor(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        store: &dyn TriggerStore,
+        current: Option<&TriggerEntry>,
+    ) -> Result<(), CommandError> {
+        let field = |style, label: &str, value: String, required| {
+            let input = CreateInputText::new(style, label, "").required(required);
+            if value.is_empty() {
+                input
+            } else {
+                input.value(value)
+            }
+        };
+        let title = match current {
+            Some(entry) => format!("「{}」を編集", truncate(entry.trigger(), 30)),
+            None => "項目を追加".to_string(),
+        };
+        let modal = CreateQuickModal::new(title)
+            .timeout(MODAL_TIMEOUT)
+            .field(field(
+                InputTextStyle::Short,
+                "キーワード",
+                current.map(|e| e.trigger().to_string()).unwrap_or_default(),
+                true,
+            ))
+            .field(field(
+                InputTextStyle::Short,
+                "別名（カンマ区切り）",
+                current
+                    .map(|e| e.keywords[1..].join(", "))
+                    .unwrap_or_default(),
+                false,
+            ))
+            .field(field(
+                InputTextStyle::Short,
+                "タイトル",
+                current.map(|e| e.title.clone()).unwrap_or_default(),
+                false,
+            ))
+            .field(field(
+                InputTextStyle::Paragraph,
+                "説明",
+                current.map(|e| e.description.clone()).unwrap_or_default(),
+                false,
+            ))
+            .field(field(
+                InputTextStyle::Paragraph,
+                "返信テキスト",
+                current.map(|e| e.response.clone()).unwrap_or_default(),
+                false,
+            ));
+        let response = interaction
+            .quick_modal(ctx, modal)
+            .await?
+            .ok_or(CommandError::Timeout("モーダルの入力"))?;
+
+        // モーダルで応答済みのため、ここから先のエラーはモーダルの送信に対して返す
+        if let Err(why) = self
+            .save(ctx, &response.interaction, store, current, &response.inputs)
+            .await
+        {
+            report_modal_error(ctx, self.name(), &response.interaction, &why).await;
+        }
+        Ok(())
+    }
+
+    async fn save(
+        &self,
+        ctx: &Context,
+        modal: &ModalInteraction,
+        store: &dyn TriggerStore,
+        current: Option<&TriggerEntry>,
+        inputs: &[String],
+    ) -> Result<(), CommandError> {
+        let [trigger, aliases, title, description, response] = inputs else {
+            return Err(CommandError::InvalidInput(
+                "モーダルの入力を読み取れませんでした。".to_string(),
+            ));
+        };
+        let fields = TriggerFields {
+            trigger: trigger.trim().to_string(),
+            aliases: aliases
+                .split([',', '、'])
+                .map(str::trim)
+                .filter(|alias| !alias.is_empty())
+                .map(str::to_string)
+                .collect(),
+            title: title.trim().to_string(),
+            description: description.trim().to_string(),
+            response: response.trim().to_string(),
+            // モーダルで入力しない項目（タグや画像など）は元の値を残す
+            ..current.map(TriggerFields::from).unwrap_or_default()
+        };
+        if fields.trigger.is_empty() {
+            return Err(CommandError::InvalidInput(
+                "キーワードを入力してください。".to_string(),
+            ));
+        }
+
+        modal.defer_ephemeral(&ctx.http).await?;
+
+        // 他のエントリと同じキーワードは登録できない
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        let current_row = current.map(|entry| entry.row);
+        for keyword in std::iter::once(&fields.trigger).chain(&fields.aliases) {
+            if let Some(existing) = table
+                .entries
+                .iter()
+                .find(|entry| Some(entry.row) != current_row && entry.matches(keyword))
+            {
+                return Err(CommandError::InvalidInput(format!(
+                    "「{}」は「{}」のキーワードとして登録済みです。",
+                    keyword,
+                    existing.trigger()
+                )));
+            }
+        }
+
+        let message = match current {
+            Some(entry) => {
+                store
+                    .update(entry.row, entry.trigger(), &fields)
+                    .await
+                    .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+                format!(":pencil: 「{}」を更新しました。", fields.trigger)
+            }
+            None => {
+                store
+                    .append(&fields)
+                    .await
+                    .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+                format!(":new: 「{}」を追加しました。", fields.trigger)
+            }
+        };
+        modal
+            .edit_response(&ctx.http, EditInteractionResponse::new().content(message))
+            .await?;
+        Ok(())
+    }
+
+    async fn add(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        store: &dyn TriggerStore,
+    ) -> Result<(), CommandError> {
+        self.open_editor(ctx, interaction, store, None).await
+    }
+
+    async fn edit(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        store: &dyn TriggerStore,
+        options: &[ResolvedOption<'_>],
+    ) -> Result<(), CommandError> {
+        let keyword = string_option(options, "keyword");
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        let Some(entry) = table.find(keyword) else {
+            return respond(
+                ctx,
+                interaction,
+                format!(":warning: 「{}」は登録されていません。", keyword),
+                true,
+            )
+            .await;
+        };
+        self.open_editor(ctx, interaction, store, Some(entry)).await
+    }
+
+    /// ランダムに選んだ項目を表示する（`tag` を指定した場合はそのタグの項目から選ぶ）
+    async fn random(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        dic_config: &DicConfig,
+        store: &dyn TriggerStore,
+        options: &[ResolvedOption<'_>],
+    ) -> Result<(), CommandError> {
+        let tag = string_option(options, "tag");
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        let candidates: Vec<&TriggerEntry> = table
+            .entries
+            .iter()
+            .filter(|entry| tag.is_empty() || entry.has_tag(tag))
+            .collect();
+        let Some(entry) = candidates.choose(&mut rand::thread_rng()) else {
+            return respond(ctx, interaction, no_entries_message(tag), true).await;
+        };
+
+        interaction.defer(&ctx.http).await?;
+        let reply = EntryReply::new(entry, entry.trigger(), dic_config);
+        interaction
+            .edit_response(&ctx.http, reply.edit_response())
+            .await?;
+        Ok(())
+    }
+
+    /// 項目の一覧をページに分けて表示する（`tag` を指定した場合はそのタグの項目だけ）
+    async fn list(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        store: &dyn TriggerStore,
+        options: &[ResolvedOption<'_>],
+    ) -> Result<(), CommandError> {
+        let tag = string_option(options, "tag");
+        let page = options
+            .iter()
+            .find(|opt| opt.name == "page")
+            .and_then(|opt| match opt.value {
+                ResolvedValue::Integer(page) => usize::try_from(page).ok(),
+                _ => None,
+            })
+            .unwrap_or(1);
+
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        let entries: Vec<&TriggerEntry> = table
+            .entries
+            .iter()
+            .filter(|entry| tag.is_empty() || entry.has_tag(tag))
+            .collect();
+        if entries.is_empty() {
+            return respond(ctx, interaction, no_entries_message(tag), true).await;
         }
+
+        interaction.defer_ephemeral(&ctx.http).await?;
+
+        let title = if tag.is_empty() {
+            "📖 辞書の一覧".to_string()
+        } else {
+            format!("📖 タグ「{}」の項目", tag)
+        };
+        let page_count = entries.len().div_ceil(LIST_PAGE_SIZE);
+        let embeds = entries
+            .chunks(LIST_PAGE_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let lines: Vec<String> = chunk
+                    .iter()
+                    .map(|entry| {
+                        if entry.title.is_empty() {
+                            format!("・`{}`", entry.trigger())
+                        } else {
+                            format!("・`{}` — {}", entry.trigger(), entry.title)
+                        }
+                    })
+                    .collect();
+                CreateEmbed::new()
+                    .title(&title)
+                    .description(lines.join("\n"))
+                    .colour(Colour::DARK_BLUE)
+                    .footer(CreateEmbedFooter::new(format!(
+                        "全{}件 📄 {}/{}",
+                        entries.len(),
+                        i + 1,
+                        page_count
+                    )))
+            })
+            .collect();
+
+        let paginator = Paginator::new(embeds).with_start_page(page.saturating_sub(1));
+        let message = paginator.send(ctx, interaction).await?;
+        paginator.wait(ctx, interaction, &message).await?;
+        Ok(())
     }
 
-    Ok(())
+    /// よく表示されている項目を表示回数とともに並べる
+    async fn top(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        store: &dyn TriggerStore,
+    ) -> Result<(), CommandError> {
+        interaction.defer(&ctx.http).await?;
+
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        // 表から消えた項目は出さない
+        let mut ranking: Vec<(&str, u32)> = table
+            .entries
+            .iter()
+            .map(|entry| (entry.trigger(), self.hits.get(entry.trigger())))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranking.truncate(TOP_LIMIT);
+
+        let description = if ranking.is_empty() {
+            "まだ表示された項目はありません。".to_string()
+        } else {
+            ranking
+                .iter()
+                .enumerate()
+                .map(|(i, (trigger, count))| format!("{}. `{}` — {}回", i + 1, trigger, count))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().embed(
+                    CreateEmbed::new()
+                        .title("🏆 よく使われている項目")
+                        .description(description)
+                        .colour(Colour::GOLD),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn refresh(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        store: &dyn TriggerStore,
+    ) -> Result<(), CommandError> {
+        interaction.defer_ephemeral(&ctx.http).await?;
+
+        let table = store
+            .refresh()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    ":arrows_counterclockwise: {}を読み込み直しました（{}件）。",
+                    store.source_name(),
+                    table.entries.len()
+                )),
+            )
+            .await?;
+        Ok(())
+    }
 }
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("dic")
-        .description("Trigger Commands")
-        .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::String,
-                "keyword",
-                "キーワードを入力してください。例) genkai, 徳井病, gomi など",
+#[async_trait]
+impl SlashCommand for Dic {
+    fn name(&self) -> &'static str {
+        "dic"
+    }
+
+    fn register(&self) -> CreateCommand {
+        let source_name = self
+            .store
+            .as_ref()
+            .map(|store| store.source_name())
+            .unwrap_or("辞書");
+        let command = CreateCommand::new(self.name())
+            .description("Trigger Commands")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "search",
+                    "キーワードに対応する内容を表示します",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "keyword",
+                        "キーワードを入力してください。例) genkai, 徳井病, gomi など",
+                    )
+                    .set_autocomplete(true)
+                    .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "random",
+                    "ランダムに選んだ項目を表示します",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "tag",
+                        "このタグの項目から選びます",
+                    )
+                    .set_autocomplete(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "list",
+                    "登録されている項目の一覧を表示します",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "tag",
+                        "このタグの項目だけを表示します",
+                    )
+                    .set_autocomplete(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "page",
+                        "最初に表示するページ",
+                    )
+                    .min_int_value(1),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "top",
+                "よく使われている項目を表示します",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "refresh",
+                format!(
+                    "{}の内容を読み込み直します（編集をすぐに反映したいとき）",
+                    source_name
+                ),
+            ));
+
+        // 書き込みが許可されている場合だけ追加・編集のサブコマンドを登録する
+        if !self.config.as_ref().is_some_and(|dic| dic.allow_edit) {
+            return command;
+        }
+        command
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "新しい項目を追加します",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "edit",
+                    "登録済みの項目を編集します",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "keyword",
+                        "編集する項目のキーワード",
+                    )
+                    .set_autocomplete(true)
+                    .required(true),
+                ),
+            )
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.dic.is_some()
+    }
+
+    fn intents(&self) -> GatewayIntents {
+        // 自動返信するチャンネルの発言を読むため
+        if self
+            .config
+            .as_ref()
+            .is_some_and(|dic| !dic.auto_response_channel_ids.is_empty())
+        {
+            GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT
+        } else {
+            GatewayIntents::empty()
+        }
+    }
+
+    /// 自動返信が有効なチャンネルの発言にキーワードが含まれていれば、その項目を返信する
+    async fn on_message(&self, ctx: &Context, message: &Message) -> Result<(), CommandError> {
+        let (Some(dic_config), Some(store)) = (&self.config, &self.store) else {
+            return Ok(());
+        };
+        if !dic_config
+            .auto_response_channel_ids
+            .contains(&message.channel_id.get())
+        {
+            return Ok(());
+        }
+
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        let Some((entry, keyword)) = table.find_in_text(&message.content) else {
+            return Ok(());
+        };
+
+        let cooldown = dic_config
+            .auto_response_cooldown_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_AUTO_RESPONSE_COOLDOWN);
+        if !self.start_auto_response(message.channel_id, entry, cooldown) {
+            return Ok(());
+        }
+
+        self.record_hit(entry).await;
+        let reply = EntryReply::new(entry, keyword, dic_config)
+            .create_message()
+            .reference_message(message)
+            // 返信のたびに通知が飛ばないようにする
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false));
+        message.channel_id.send_message(&ctx.http, reply).await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let (Some(dic_config), Some(store)) = (&self.config, self.store.as_deref()) else {
+            return respond(
+                ctx,
+                interaction,
+                ":warning: dic コマンドが設定されていません。".to_string(),
+                true,
             )
-            .required(true),
-        )
+            .await;
+        };
+
+        let options = interaction.data.options();
+        let Some(ResolvedOption {
+            name: subcommand,
+            value: ResolvedValue::SubCommand(sub_options),
+            ..
+        }) = options.first()
+        else {
+            return Err(CommandError::InvalidInput(
+                "サブコマンドを指定してください。".to_string(),
+            ));
+        };
+
+        match *subcommand {
+            "search" => {
+                self.search(ctx, interaction, dic_config, store, sub_options)
+                    .await
+            }
+            "random" => {
+                self.random(ctx, interaction, dic_config, store, sub_options)
+                    .await
+            }
+            "list" => self.list(ctx, interaction, store, sub_options).await,
+            "top" => self.top(ctx, interaction, store).await,
+            "refresh" => self.refresh(ctx, interaction, store).await,
+            "add" | "edit" if !dic_config.allow_edit => Err(CommandError::InvalidInput(
+                "項目の追加・編集は有効になっていません。".to_string(),
+            )),
+            "add" | "edit" if !Self::can_edit(interaction, dic_config) => {
+                respond(
+                    ctx,
+                    interaction,
+                    ":no_entry: 項目を追加・編集する権限がありません。".to_string(),
+                    true,
+                )
+                .await
+            }
+            "add" => self.add(ctx, interaction, store).await,
+            "edit" => self.edit(ctx, interaction, store, sub_options).await,
+            _ => Err(CommandError::InvalidInput(format!(
+                "不明なサブコマンドです: {}",
+                subcommand
+            ))),
+        }
+    }
+
+    async fn autocomplete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let Some(focused) = interaction.data.autocomplete() else {
+            return Ok(());
+        };
+        let table = store
+            .table()
+            .await
+            .map_err(|e| CommandError::upstream(store.service_name(), e))?;
+        let choices = match focused.name {
+            "keyword" => self.suggest(&table.entries, focused.value),
+            "tag" => suggest_tags(&table.entries, focused.value),
+            _ => return Ok(()),
+        };
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Autocomplete(
+                    CreateAutocompleteResponse::new().set_choices(choices),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// 設定されたファイルから表示回数を読み込む
+fn load_hits(config: &Config) -> HitCounter {
+    let Some(dic) = &config.dic else {
+        return HitCounter::new(DEFAULT_HITS_PATH);
+    };
+    let path = dic
+        .hits_path
+        .clone()
+        .unwrap_or_else(|| DEFAULT_HITS_PATH.to_string());
+    HitCounter::load(&path).unwrap_or_else(|e| {
+        eprintln!("[/dic] Failed to load hit counts from {path}, starting empty: {e}");
+        HitCounter::new(&path)
+    })
+}
+
+fn no_entries_message(tag: &str) -> String {
+    if tag.is_empty() {
+        ":warning: 項目が登録されていません。".to_string()
+    } else {
+        format!(":warning: タグ「{}」の項目はありません。", tag)
+    }
+}
+
+/// 入力中の文字列を含むタグを候補として返す（前方一致を優先）
+fn suggest_tags(entries: &[TriggerEntry], query: &str) -> Vec<AutocompleteChoice> {
+    let query = normalize_keyword(query);
+    let mut tags: Vec<&str> = entries
+        .iter()
+        .flat_map(|entry| entry.tags.iter().map(String::as_str))
+        .filter(|tag| normalize_keyword(tag).contains(&query))
+        .collect();
+    tags.sort_by_key(|tag| (!normalize_keyword(tag).starts_with(&query), *tag));
+    tags.dedup();
+    tags.into_iter()
+        .take(AUTOCOMPLETE_LIMIT)
+        .map(|tag| AutocompleteChoice::new(truncate(tag, CHOICE_NAME_LIMIT), tag))
+        .collect()
+}
+
+/// エントリの表示内容（`response` があればそのテキスト、なければ埋め込み）
+enum EntryReply {
+    Text(String),
+    Embed(Box<CreateEmbed>),
+}
+
+impl EntryReply {
+    fn new(entry: &TriggerEntry, keyword: &str, dic_config: &DicConfig) -> Self {
+        if !entry.response.is_empty() {
+            return EntryReply::Text(entry.response.clone());
+        }
+
+        let colour = entry.color.map(Colour::new).unwrap_or(Colour::DARK_BLUE);
+        let mut embed = CreateEmbed::new().colour(colour);
+
+        if !entry.title.is_empty() {
+            embed = embed.title(&entry.title);
+        }
+        if !entry.url.is_empty() {
+            embed = embed.url(&entry.url);
+        }
+        if !entry.description.is_empty() {
+            let description = match &dic_config.db_spreadsheet_url {
+                Some(url) => format!("{}\n\n[Check DB]({})", entry.description, url),
+                None => entry.description.clone(),
+            };
+            embed = embed.description(description);
+        }
+        if !entry.thumbnail_url.is_empty() {
+            embed = embed.thumbnail(&entry.thumbnail_url);
+        }
+        if !entry.image_url.is_empty() {
+            embed = embed.image(&entry.image_url);
+        }
+        let footer = if entry.footer.is_empty() {
+            format!("Keyword: {}", keyword)
+        } else {
+            entry.footer.clone()
+        };
+        EntryReply::Embed(Box::new(embed.footer(CreateEmbedFooter::new(footer))))
+    }
+
+    fn edit_response(self) -> EditInteractionResponse {
+        match self {
+            EntryReply::Text(text) => EditInteractionResponse::new().content(text),
+            EntryReply::Embed(embed) => EditInteractionResponse::new().embed(*embed),
+        }
+    }
+
+    /// チャットの発言への返信として送る内容
+    fn create_message(self) -> CreateMessage {
+        match self {
+            EntryReply::Text(text) => CreateMessage::new().content(text),
+            EntryReply::Embed(embed) => CreateMessage::new().embed(*embed),
+        }
+    }
+
+    /// ボタンを押したときに元のメッセージを置き換える内容（ボタンは消す）
+    fn update_message(self) -> CreateInteractionResponseMessage {
+        let message = CreateInteractionResponseMessage::new().components(vec![]);
+        match self {
+            EntryReply::Text(text) => message.content(text),
+            EntryReply::Embed(embed) => message.content("").embed(*embed),
+        }
+    }
 }
@@ -0,0 +1,223 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use nidaime_takohachi::llm::LlmError;
+use nidaime_takohachi::rate_limit::RateLimitError;
+use serenity::builder::{
+    CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+    EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, ModalInteraction};
+use serenity::prelude::*;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// コマンド実行時のエラー
+#[derive(Debug)]
+pub enum CommandError {
+    /// ユーザーの入力待ちや外部 API へのリクエストがタイムアウトした（何がタイムアウトしたかを持つ）
+    Timeout(&'static str),
+    /// 外部 API（Gemini, Google Sheets など）の呼び出しに失敗した
+    Upstream {
+        service: &'static str,
+        source: BoxError,
+    },
+    /// ユーザーの入力が受け付けられない（メッセージはそのままユーザーに表示する）
+    InvalidInput(String),
+    /// 利用回数の制限を超えた
+    RateLimited(RateLimitError),
+    /// Discord への応答の送信などに失敗した
+    Discord(serenity::Error),
+    /// その他のエラー
+    Other(BoxError),
+}
+
+impl CommandError {
+    /// 外部 API のエラーを包む。reqwest や LLM クライアントのタイムアウトは `Timeout` として扱う。
+    pub fn upstream(service: &'static str, source: impl Into<BoxError>) -> Self {
+        let source = source.into();
+        let is_timeout = source
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout())
+            || matches!(source.downcast_ref::<LlmError>(), Some(LlmError::Timeout));
+        if is_timeout {
+            return CommandError::Timeout(service);
+        }
+        CommandError::Upstream { service, source }
+    }
+
+    /// ユーザーに表示するエラーメッセージを返す
+    pub fn user_message(&self) -> String {
+        match self {
+            CommandError::Timeout(what) => {
+                format!(":hourglass: {what}がタイムアウトしました。もう一度お試しください。")
+            }
+            CommandError::Upstream { service, .. } => format!(
+                ":warning: {service}の呼び出しに失敗しました。しばらくしてから再度お試しください。"
+            ),
+            CommandError::InvalidInput(message) => format!(":warning: {message}"),
+            CommandError::RateLimited(e) => {
+                let wait = format_wait(e.retry_after());
+                match e {
+                    RateLimitError::Cooldown(_) => format!(
+                        ":hourglass_flowing_sand: 続けて使うことはできません。{wait}後にもう一度お試しください。"
+                    ),
+                    RateLimitError::UserDailyLimit(_) => format!(
+                        ":hourglass_flowing_sand: 今日の利用回数の上限に達しました。{wait}後にリセットされます。"
+                    ),
+                    RateLimitError::GuildDailyLimit(_) => format!(
+                        ":hourglass_flowing_sand: サーバー全体の今日の利用回数の上限に達しました。{wait}後にリセットされます。"
+                    ),
+                }
+            }
+            CommandError::Discord(_) | CommandError::Other(_) => {
+                ":warning: コマンドの実行中にエラーが発生しました。".to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Timeout(what) => write!(f, "timed out: {what}"),
+            CommandError::Upstream { service, source } => {
+                write!(f, "{service} request failed: {source}")
+            }
+            CommandError::InvalidInput(message) => write!(f, "invalid input: {message}"),
+            CommandError::RateLimited(e) => write!(f, "rate limited: {e}"),
+            CommandError::Discord(e) => write!(f, "Discord error: {e}"),
+            CommandError::Other(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for CommandError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CommandError::Timeout(_) | CommandError::InvalidInput(_) => None,
+            CommandError::Upstream { source, .. } | CommandError::Other(source) => {
+                Some(source.as_ref())
+            }
+            CommandError::RateLimited(e) => Some(e),
+            CommandError::Discord(e) => Some(e),
+        }
+    }
+}
+
+/// 待ち時間を「N分」のような表記にする（切り上げ）
+fn format_wait(duration: Duration) -> String {
+    let secs = duration.as_secs().max(1);
+    if secs < 60 {
+        return format!("{secs}秒");
+    }
+    let minutes = secs.div_ceil(60);
+    if minutes < 60 {
+        format!("{minutes}分")
+    } else {
+        format!("{}時間{}分", minutes / 60, minutes % 60)
+    }
+}
+
+impl From<serenity::Error> for CommandError {
+    fn from(e: serenity::Error) -> Self {
+        CommandError::Discord(e)
+    }
+}
+
+impl From<BoxError> for CommandError {
+    fn from(e: BoxError) -> Self {
+        CommandError::Other(e)
+    }
+}
+
+/// コマンドの失敗をログに出力し、ユーザーにエラーメッセージを返す
+///
+/// まだ応答していなければエフェメラルなメッセージで応答し、
+/// defer 済みなら元の応答をエラーメッセージで置き換える。
+/// どちらもできない場合（モーダルで応答済みなど）はエフェメラルなフォローアップを送る。
+pub async fn report_error(ctx: &Context, interaction: &CommandInteraction, error: &CommandError) {
+    eprintln!(
+        "[/{}] Command failed - user: {} ({}), guild: {}, error: {}",
+        interaction.data.name,
+        interaction.user.name,
+        interaction.user.id,
+        interaction
+            .guild_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "DM".to_string()),
+        error
+    );
+
+    let message = error.user_message();
+
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(&message)
+            .ephemeral(true),
+    );
+    if interaction
+        .create_response(&ctx.http, response)
+        .await
+        .is_ok()
+    {
+        return;
+    }
+
+    let edit = EditInteractionResponse::new()
+        .content(&message)
+        .embeds(vec![])
+        .components(vec![]);
+    if interaction.edit_response(&ctx.http, edit).await.is_ok() {
+        return;
+    }
+
+    let followup = CreateInteractionResponseFollowup::new()
+        .content(&message)
+        .ephemeral(true);
+    if let Err(why) = interaction.create_followup(&ctx.http, followup).await {
+        eprintln!(
+            "[/{}] Cannot report error to user: {why}",
+            interaction.data.name
+        );
+    }
+}
+
+/// モーダルの送信後に起きたエラーをログに出力し、モーダルの送信に対する応答としてユーザーに伝える
+///
+/// モーダルを表示したコマンドには応答済みのため、`report_error` の代わりに使う。
+pub async fn report_modal_error(
+    ctx: &Context,
+    command_name: &str,
+    modal: &ModalInteraction,
+    error: &CommandError,
+) {
+    eprintln!(
+        "[/{}] Modal submission failed - user: {} ({}), guild: {}, error: {}",
+        command_name,
+        modal.user.name,
+        modal.user.id,
+        modal
+            .guild_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "DM".to_string()),
+        error
+    );
+
+    let message = error.user_message();
+
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(&message)
+            .ephemeral(true),
+    );
+    if modal.create_response(&ctx.http, response).await.is_ok() {
+        return;
+    }
+
+    let edit = EditInteractionResponse::new().content(&message);
+    if let Err(why) = modal.edit_response(&ctx.http, edit).await {
+        eprintln!("[/{}] Cannot report error to user: {why}", command_name);
+    }
+}
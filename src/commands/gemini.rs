@@ -1,222 +1,509 @@
-use reqwest::Client;
-use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
 use serenity::builder::*;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
-use crate::config::Config;
+use super::gemini_preset::respond_preset_names;
+use super::paginator::{
+    EMBED_DESCRIPTION_LIMIT, EMBED_TITLE_LIMIT, Paginator, split_markdown, truncate,
+};
+use super::{CommandError, SlashCommand};
+use crate::config::{Config, GeminiConfig};
+use nidaime_takohachi::gemini::PresetStore;
+use nidaime_takohachi::llm::{
+    ConversationStore, LlmError, LlmProvider, LlmRequest, Message as LlmMessage, MessagePart, Usage,
+};
+use nidaime_takohachi::rate_limit::RateLimiter;
 
 const DEFAULT_CHARACTER: &str = "あなたはチャットコミュニティのみんなに愛されるBotです。みんなからくるいろんな質問にバッチリ答えてね。";
-const GEMINI_MODEL: &str = "gemini-2.5-flash";
 
-#[derive(Deserialize)]
-struct GeminiResponse {
-    candidates: Vec<Candidate>,
-}
+/// ストリーミング中に応答を書き換える間隔（Discord のレート制限に収まるようにする）
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_millis(1500);
 
-#[derive(Deserialize)]
-struct Candidate {
-    content: Content,
-}
+/// Discord のスレッド名の上限文字数
+const THREAD_NAME_LIMIT: usize = 100;
+
+/// 画像として渡せる MIME タイプ
+const IMAGE_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/webp",
+    "image/heic",
+    "image/heif",
+];
+/// テキストとして扱うファイルの拡張子（Content-Type が付いていない場合の判定用）
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "log", "md", "csv", "json", "yaml", "yml", "toml", "xml", "rs", "py", "js", "ts",
+];
+const MAX_IMAGE_BYTES: u32 = 8 * 1024 * 1024;
+const MAX_TEXT_BYTES: u32 = 512 * 1024;
 
-#[derive(Deserialize)]
-struct Content {
-    parts: Vec<Part>,
+pub struct Gemini {
+    llm: Option<Arc<dyn LlmProvider>>,
+    /// スレッドでの会話を続けられるか（`gemini.thread_conversations`）
+    thread_conversations: bool,
+    conversations: ConversationStore,
+    presets: Arc<PresetStore>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
-#[derive(Deserialize)]
-struct Part {
-    text: String,
+impl Gemini {
+    pub fn new(
+        config: &Config,
+        llm: Option<Arc<dyn LlmProvider>>,
+        presets: Arc<PresetStore>,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            rate_limiter,
+            llm,
+            presets,
+            thread_conversations: config
+                .gemini
+                .as_ref()
+                .is_some_and(|gemini| gemini.thread_conversations),
+            conversations: config
+                .gemini
+                .as_ref()
+                .map(GeminiConfig::conversation_store)
+                .unwrap_or_default(),
+        }
+    }
 }
 
-pub async fn run(ctx: &Context, interaction: &CommandInteraction) -> Result<(), serenity::Error> {
-    interaction.defer(&ctx.http).await?;
-
-    let key = interaction
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "key")
-        .and_then(|opt| opt.value.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let character = interaction
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "character")
-        .and_then(|opt| opt.value.as_str())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| DEFAULT_CHARACTER.to_string());
-
-    // Load config in a block so the non-Send error is dropped before any await
-    let api_key = {
-        match Config::load() {
-            Ok(c) => c.gemini.map(|g| g.api_key),
-            Err(e) => {
-                println!("Failed to load config: {}", e);
-                None
-            }
+#[async_trait]
+impl SlashCommand for Gemini {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn register(&self) -> CreateCommand {
+        let command = CreateCommand::new(self.name())
+            .description("Geminiに質問をしましょう！")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "key", "質問内容").required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "character",
+                    "保存したキャラ設定の名前か、「あなたは～です。」の形で性格やキャラを入力してください。",
+                )
+                .set_autocomplete(true)
+                .required(false),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Attachment,
+                    "attachment",
+                    "質問と一緒に渡す画像 (PNG/JPEG/WebP/HEIC, 8MB まで) かテキストファイル (512KB まで)",
+                )
+                .required(false),
+            );
+        if !self.thread_conversations {
+            return command;
         }
-    };
+        command.add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "thread",
+                "回答からスレッドを作成し、スレッド内で会話を続けます",
+            )
+            .required(false),
+        )
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.gemini.is_some()
+    }
+
+    fn intents(&self) -> GatewayIntents {
+        // スレッド内の発言を読み取って会話を続けるため（特権 Intent なので設定で有効にしたときだけ要求する）
+        if self.thread_conversations {
+            GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT
+        } else {
+            GatewayIntents::empty()
+        }
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        // 制限を超えた場合はエフェメラルで返したいので defer より前に確認する
+        let reservation = self
+            .rate_limiter
+            .check(interaction.user.id.get())
+            .map_err(CommandError::RateLimited)?;
+
+        interaction.defer(&ctx.http).await?;
+
+        let key = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "key")
+            .and_then(|opt| opt.value.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let character_option = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "character")
+            .and_then(|opt| opt.value.as_str());
+
+        // 保存したキャラ設定の名前が指定されていればその内容を使う
+        let preset = character_option.and_then(|name| {
+            let guild_id = interaction.guild_id?.get();
+            self.presets.get(guild_id, name)
+        });
+        let (character, character_display) = match (preset, character_option) {
+            (Some(preset), _) => (preset.prompt, preset.name),
+            (None, Some(character)) => (character.to_string(), character.to_string()),
+            (None, None) => (DEFAULT_CHARACTER.to_string(), "Default".to_string()),
+        };
 
-    let api_key = match api_key {
-        Some(k) => k,
-        None => {
+        let start_thread = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "thread")
+            .and_then(|opt| opt.value.as_bool())
+            .unwrap_or(false)
+            && self.thread_conversations;
+
+        let Some(llm) = &self.llm else {
             interaction
                 .edit_response(
                     &ctx.http,
-                    EditInteractionResponse::new()
-                        .content(":warning: Gemini API キーが設定されていません。"),
+                    EditInteractionResponse::new().content(":warning: AI の設定がありません。"),
                 )
                 .await?;
             return Ok(());
+        };
+
+        let attachment = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "attachment")
+            .and_then(|opt| opt.value.as_attachment_id())
+            .and_then(|id| interaction.data.resolved.attachments.get(&id));
+
+        let mut parts = vec![MessagePart::Text(key.clone())];
+        if let Some(attachment) = attachment {
+            parts.push(attachment_part(attachment).await?);
         }
-    };
+        let question = LlmMessage::user_parts(parts);
 
-    let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-        GEMINI_MODEL, api_key
-    );
+        let request = LlmRequest::new(vec![question.clone()]).with_system(&character);
+        let title = truncate(&format!("Q. {}", key), EMBED_TITLE_LIMIT);
+        let answer = stream_answer(ctx, interaction, llm.as_ref(), &request, &title).await?;
+        reservation.commit();
 
-    let payload = serde_json::json!({
-        "contents": [
-            {
-                "parts": [
-                    {"text": character},
-                    {"text": key}
-                ]
-            }
-        ]
-    });
-
-    let client = Client::new();
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(120))
-        .send()
-        .await;
-
-    let answer = match response {
-        Ok(res) => {
-            let status = res.status();
-            let body = match res.text().await {
-                Ok(t) => t,
-                Err(e) => {
-                    println!("Failed to read Gemini response body: {}", e);
-                    interaction
-                        .edit_response(
-                            &ctx.http,
-                            EditInteractionResponse::new().content(
-                                ":warning: Gemini APIのレスポンス読み取りに失敗しました。",
-                            ),
-                        )
-                        .await?;
-                    return Ok(());
-                }
-            };
-
-            if !status.is_success() {
-                println!("Gemini API error: HTTP {} - {}", status, body);
-                interaction
-                    .edit_response(
-                        &ctx.http,
-                        EditInteractionResponse::new().content(format!(
-                            ":warning: Gemini APIがエラーを返しました。(HTTP {})",
-                            status
-                        )),
-                    )
-                    .await?;
-                return Ok(());
-            }
+        let mut footer = format!(
+            " Model: {}\n🪀 キャラ設定: {}{}",
+            llm.model(),
+            character_display,
+            format_usage(answer.usage)
+        );
+        if answer.interrupted {
+            footer.push_str("\n⚠️ 応答が途中で途切れました");
+        }
+        let paginator =
+            Paginator::from_text(&title, &answer.text, Colour::DARK_GREEN).with_footer(&footer);
+        let message = paginator.send(ctx, interaction).await?;
 
-            match serde_json::from_str::<GeminiResponse>(&body) {
-                Ok(gemini_res) => {
-                    match gemini_res
-                        .candidates
-                        .into_iter()
-                        .next()
-                        .and_then(|c| c.content.parts.into_iter().next())
-                        .map(|p| p.text)
-                    {
-                        Some(text) => text,
-                        None => {
-                            println!("Gemini response has no candidates/parts: {}", body);
-                            interaction
-                                .edit_response(
-                                    &ctx.http,
-                                    EditInteractionResponse::new().content(
-                                        ":warning: Gemini APIからの応答を解析できませんでした。",
-                                    ),
-                                )
-                                .await?;
-                            return Ok(());
-                        }
-                    }
+        if start_thread {
+            // スレッドを作れなくても（権限がない、スレッドや DM の中で使ったなど）回答は残す
+            let thread_name = truncate(&key, THREAD_NAME_LIMIT);
+            let thread = interaction
+                .channel_id
+                .create_thread_from_message(
+                    &ctx.http,
+                    message.id,
+                    CreateThread::new(thread_name)
+                        .auto_archive_duration(AutoArchiveDuration::OneHour),
+                )
+                .await;
+            match thread {
+                Ok(thread) => {
+                    self.conversations
+                        .start(thread.id.get(), character, question, answer.text);
                 }
                 Err(e) => {
-                    println!("Failed to parse Gemini response ({}): {}", e, body);
-                    interaction
-                        .edit_response(
-                            &ctx.http,
-                            EditInteractionResponse::new()
-                                .content(":warning: Gemini APIのレスポンスの解析に失敗しました。"),
-                        )
-                        .await?;
-                    return Ok(());
+                    eprintln!("[/gemini] Failed to create thread: {}", e);
+                    let followup = CreateInteractionResponseFollowup::new()
+                        .content(":warning: スレッドを作成できませんでした。このチャンネルではスレッドを使えないか、Bot に権限がありません。")
+                        .ephemeral(true);
+                    interaction.create_followup(&ctx.http, followup).await?;
                 }
             }
         }
-        Err(e) => {
-            println!("Gemini API request failed: {}", e);
-            interaction
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new()
-                        .content(":warning: Gemini APIへのリクエストに失敗しました。"),
-                )
-                .await?;
+
+        paginator.wait(ctx, interaction, &message).await?;
+
+        Ok(())
+    }
+
+    async fn autocomplete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(focused) = interaction.data.autocomplete() else {
+            return Ok(());
+        };
+        if focused.name != "character" {
             return Ok(());
         }
-    };
+        respond_preset_names(ctx, interaction, &self.presets, focused.value).await
+    }
 
-    let character_display = if character == DEFAULT_CHARACTER {
-        "Default".to_string()
-    } else {
-        character
+    async fn on_message(&self, ctx: &Context, message: &Message) -> Result<(), CommandError> {
+        if !self.thread_conversations {
+            return Ok(());
+        }
+        let Some(llm) = &self.llm else {
+            return Ok(());
+        };
+        let Some(request) = self
+            .conversations
+            .request_for(message.channel_id.get(), &message.content)
+        else {
+            return Ok(());
+        };
+
+        let reservation = match self.rate_limiter.check(message.author.id.get()) {
+            Ok(reservation) => reservation,
+            Err(e) => {
+                let error = CommandError::RateLimited(e);
+                message.reply(&ctx.http, error.user_message()).await?;
+                return Ok(());
+            }
+        };
+
+        let _typing = message.channel_id.start_typing(&ctx.http);
+
+        let answer = match llm.generate(&request).await {
+            Ok(answer) => answer,
+            Err(e) => {
+                let error = CommandError::upstream(llm.service_name(), e);
+                message.reply(&ctx.http, error.user_message()).await?;
+                return Err(error);
+            }
+        };
+        reservation.commit();
+
+        let footer = format!(" Model: {}{}", llm.model(), format_usage(answer.usage));
+
+        // スレッド内では長い回答を複数のメッセージに分けて送る
+        for (i, page) in split_markdown(&answer.text, EMBED_DESCRIPTION_LIMIT)
+            .into_iter()
+            .enumerate()
+        {
+            let mut builder = CreateMessage::new().embed(
+                CreateEmbed::new()
+                    .description(page)
+                    .colour(Colour::DARK_GREEN)
+                    .footer(CreateEmbedFooter::new(&footer)),
+            );
+            if i == 0 {
+                builder = builder.reference_message(message);
+            }
+            message.channel_id.send_message(&ctx.http, builder).await?;
+        }
+
+        self.conversations
+            .record(message.channel_id.get(), &message.content, answer.text);
+
+        Ok(())
+    }
+}
+
+/// ストリーミングで受け取った回答
+struct StreamedAnswer {
+    text: String,
+    usage: Option<Usage>,
+    /// ストリームが途中で切れ、回答が途中までしかない
+    interrupted: bool,
+}
+
+/// 回答をストリーミングで受け取りながら、defer 済みの応答を一定間隔で書き換える
+///
+/// 途中でストリームが切れた場合は、それまでに受け取った分を回答として返す。
+async fn stream_answer(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    llm: &dyn LlmProvider,
+    request: &LlmRequest,
+    title: &str,
+) -> Result<StreamedAnswer, CommandError> {
+    let mut stream = llm
+        .stream(request)
+        .await
+        .map_err(|e| CommandError::upstream(llm.service_name(), e))?;
+
+    let mut answer = StreamedAnswer {
+        text: String::new(),
+        usage: None,
+        interrupted: false,
     };
+    let mut last_edit = Instant::now();
 
-    let embed = CreateEmbed::new()
-        .title(format!("Q. {}", key))
-        .description(answer)
-        .colour(Colour::DARK_GREEN)
-        .footer(CreateEmbedFooter::new(format!(
-            " Model: {}\n🪀 キャラ設定: {}",
-            GEMINI_MODEL, character_display
-        )));
+    while let Some(chunk) = stream.next_chunk().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) if !answer.text.is_empty() => {
+                eprintln!("[/gemini] Stream was interrupted: {}", e);
+                answer.interrupted = true;
+                break;
+            }
+            Err(e) => return Err(CommandError::upstream(llm.service_name(), e)),
+        };
+
+        answer.text.push_str(&chunk.text);
+        if chunk.usage.is_some() {
+            answer.usage = chunk.usage;
+        }
 
-    interaction
-        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
-        .await?;
+        if !answer.text.is_empty() && last_edit.elapsed() >= STREAM_EDIT_INTERVAL {
+            // 生成中は最後のページだけを表示する
+            let pages = split_markdown(&answer.text, EMBED_DESCRIPTION_LIMIT);
+            let embed = CreateEmbed::new()
+                .title(title)
+                .description(pages.last().cloned().unwrap_or_default())
+                .colour(Colour::DARK_GREEN)
+                .footer(CreateEmbedFooter::new("✍️ 生成中…"));
+            // 途中経過の表示に失敗しても、回答の受け取りは続ける
+            if let Err(e) = interaction
+                .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+                .await
+            {
+                eprintln!("[/gemini] Failed to show streamed progress: {}", e);
+            }
+            last_edit = Instant::now();
+        }
+    }
 
-    Ok(())
+    if answer.text.is_empty() {
+        return Err(CommandError::upstream(
+            llm.service_name(),
+            LlmError::EmptyResponse,
+        ));
+    }
+
+    Ok(answer)
 }
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("gemini")
-        .description("Geminiに質問をしましょう！")
-        .add_option(
-            CreateCommandOption::new(CommandOptionType::String, "key", "質問内容").required(true),
-        )
-        .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::String,
-                "character",
-                "Geminiに性格やキャラを与えることができます。必ず「あなたは～です。」と書いてください。",
+/// 添付ファイルをダウンロードして LLM に渡すパートにする
+///
+/// 画像はそのまま画像として、テキストファイルは本文をテキストとして渡す。
+async fn attachment_part(attachment: &Attachment) -> Result<MessagePart, CommandError> {
+    let content_type = attachment
+        .content_type
+        .as_deref()
+        .map(|t| t.split(';').next().unwrap_or(t).trim().to_lowercase());
+
+    if let Some(mime_type) = content_type
+        .as_deref()
+        .filter(|t| IMAGE_MIME_TYPES.contains(t))
+    {
+        if attachment.size > MAX_IMAGE_BYTES {
+            return Err(CommandError::InvalidInput(format!(
+                "画像が大きすぎます。{}MB 以下のファイルを添付してください。",
+                MAX_IMAGE_BYTES / 1024 / 1024
+            )));
+        }
+        let data = attachment.download().await?;
+        return Ok(MessagePart::Image {
+            mime_type: mime_type.to_string(),
+            data,
+        });
+    }
+
+    if is_text_attachment(attachment, content_type.as_deref()) {
+        if attachment.size > MAX_TEXT_BYTES {
+            return Err(CommandError::InvalidInput(format!(
+                "テキストファイルが大きすぎます。{}KB 以下のファイルを添付してください。",
+                MAX_TEXT_BYTES / 1024
+            )));
+        }
+        let data = attachment.download().await?;
+        let text = String::from_utf8(data).map_err(|_| {
+            CommandError::InvalidInput(
+                "テキストファイルを UTF-8 として読み込めませんでした。".to_string(),
             )
-            .required(false),
-        )
+        })?;
+        return Ok(MessagePart::Text(fenced_attachment(
+            &attachment.filename,
+            &text,
+        )));
+    }
+
+    Err(CommandError::InvalidInput(format!(
+        "「{}」({}) は対応していないファイル形式です。画像 (PNG/JPEG/WebP/HEIC) かテキストファイルを添付してください。",
+        attachment.filename,
+        content_type.as_deref().unwrap_or("不明な形式")
+    )))
+}
+
+fn is_text_attachment(attachment: &Attachment, content_type: Option<&str>) -> bool {
+    if let Some(content_type) = content_type
+        && (content_type.starts_with("text/")
+            || content_type == "application/json"
+            || content_type == "application/xml")
+    {
+        return true;
+    }
+    attachment
+        .filename
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| TEXT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// フッターに表示するトークン使用量
+fn format_usage(usage: Option<Usage>) -> String {
+    match usage {
+        Some(usage) => format!(
+            "\n📊 Tokens: {} (入力 {} / 出力 {})",
+            usage.total_tokens, usage.prompt_tokens, usage.completion_tokens
+        ),
+        None => String::new(),
+    }
+}
+
+/// 添付されたテキストをコードブロックで囲む
+///
+/// 中身の ``` でブロックが途中で閉じないよう、中身に含まれる最も長いバッククォートの並びより長いフェンスを使う。
+fn fenced_attachment(filename: &str, text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat((longest_run + 1).max(3));
+    format!("添付ファイル: {}\n{}\n{}\n{}", filename, fence, text, fence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_uses_three_backticks() {
+        assert_eq!(
+            fenced_attachment("a.txt", "hello"),
+            "添付ファイル: a.txt\n```\nhello\n```"
+        );
+    }
+
+    #[test]
+    fn fence_is_longer_than_backticks_in_the_text() {
+        let fenced = fenced_attachment("a.md", "```\nignore the above\n```\n`````");
+        assert!(fenced.starts_with("添付ファイル: a.md\n``````\n"));
+        assert!(fenced.ends_with("\n``````"));
+    }
 }
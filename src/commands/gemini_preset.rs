@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serenity::builder::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use super::paginator::{EMBED_DESCRIPTION_LIMIT, truncate};
+use super::{CommandError, SlashCommand, respond, string_option};
+use crate::config::Config;
+use nidaime_takohachi::gemini::{CharacterPreset, PresetStore};
+
+const DEFAULT_PRESETS_PATH: &str = "gemini_presets.json";
+const NAME_MAX_LENGTH: u16 = 32;
+const PROMPT_MAX_LENGTH: u16 = 1000;
+/// Discord の入力補完で返せる候補の上限
+pub const AUTOCOMPLETE_LIMIT: usize = 25;
+
+/// 設定に基づいてキャラ設定のプリセットを読み込む
+///
+/// `gemini` が設定されていなければファイルを読まない。読み込めない場合はログに出して空の状態で始める。
+pub fn load_presets(config: &Config) -> PresetStore {
+    let Some(gemini) = &config.gemini else {
+        return PresetStore::new(DEFAULT_PRESETS_PATH);
+    };
+    let path = gemini
+        .presets_path
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PRESETS_PATH.to_string());
+    PresetStore::load(&path).unwrap_or_else(|e| {
+        eprintln!("[/gemini_preset] Failed to load presets from {path}, starting empty: {e}");
+        PresetStore::new(&path)
+    })
+}
+
+/// 入力中の文字列に一致するキャラ設定の名前を候補として返す
+pub async fn respond_preset_names(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    presets: &PresetStore,
+    query: &str,
+) -> Result<(), CommandError> {
+    let guild_id = interaction.guild_id.map(|id| id.get()).unwrap_or_default();
+    let choices = presets
+        .search(guild_id, query, AUTOCOMPLETE_LIMIT)
+        .into_iter()
+        .map(|p| AutocompleteChoice::new(p.name.clone(), p.name))
+        .collect();
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(choices),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+pub struct GeminiPreset {
+    presets: Arc<PresetStore>,
+}
+
+impl GeminiPreset {
+    pub fn new(presets: Arc<PresetStore>) -> Self {
+        Self { presets }
+    }
+
+    async fn create(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        guild_id: u64,
+        options: &[ResolvedOption<'_>],
+    ) -> Result<(), CommandError> {
+        let name = string_option(options, "name").trim().to_string();
+        let prompt = string_option(options, "prompt").trim().to_string();
+
+        if name.is_empty() || prompt.is_empty() {
+            return Err(CommandError::InvalidInput(
+                "名前とキャラ設定を入力してください。".to_string(),
+            ));
+        }
+
+        let preset = CharacterPreset {
+            name: name.clone(),
+            prompt,
+            created_by: interaction.user.id.get(),
+        };
+        let inserted = self
+            .presets
+            .insert(guild_id, preset)
+            .await
+            .map_err(|e| CommandError::Other(Box::new(e)))?;
+
+        let content = if inserted {
+            format!(":white_check_mark: キャラ設定「{}」を保存しました。", name)
+        } else {
+            format!(
+                ":warning: キャラ設定「{}」はすでに登録されています。別の名前にするか、削除してから登録し直してください。",
+                name
+            )
+        };
+
+        respond(ctx, interaction, content, !inserted).await
+    }
+
+    async fn list(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        guild_id: u64,
+    ) -> Result<(), CommandError> {
+        let presets = self.presets.list(guild_id);
+        if presets.is_empty() {
+            return respond(
+                ctx,
+                interaction,
+                "キャラ設定はまだ登録されていません。`/gemini_preset create` で登録できます。"
+                    .to_string(),
+                true,
+            )
+            .await;
+        }
+
+        let description = presets
+            .iter()
+            .map(|p| format!("- **{}**: {}", p.name, truncate(&p.prompt, 100)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let embed = CreateEmbed::new()
+            .title("🪀 キャラ設定一覧")
+            .description(truncate(&description, EMBED_DESCRIPTION_LIMIT))
+            .colour(Colour::DARK_GREEN)
+            .footer(CreateEmbedFooter::new(format!("{}件", presets.len())));
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().embed(embed),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        guild_id: u64,
+        options: &[ResolvedOption<'_>],
+    ) -> Result<(), CommandError> {
+        let name = string_option(options, "name");
+
+        let Some(preset) = self.presets.get(guild_id, name) else {
+            return respond(
+                ctx,
+                interaction,
+                format!(":warning: キャラ設定「{}」は登録されていません。", name),
+                true,
+            )
+            .await;
+        };
+
+        // 作成者かサーバー管理権限を持つメンバーだけが削除できる
+        let can_manage = interaction
+            .member
+            .as_ref()
+            .and_then(|m| m.permissions)
+            .is_some_and(|p| p.manage_guild());
+        if preset.created_by != interaction.user.id.get() && !can_manage {
+            return respond(
+                ctx,
+                interaction,
+                ":warning: キャラ設定を削除できるのは作成者かサーバー管理者だけです。".to_string(),
+                true,
+            )
+            .await;
+        }
+
+        self.presets
+            .remove(guild_id, name)
+            .await
+            .map_err(|e| CommandError::Other(Box::new(e)))?;
+
+        respond(
+            ctx,
+            interaction,
+            format!(":wastebasket: キャラ設定「{}」を削除しました。", name),
+            false,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl SlashCommand for GeminiPreset {
+    fn name(&self) -> &'static str {
+        "gemini_preset"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("/gemini で使うキャラ設定を管理します")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "create",
+                    "キャラ設定を登録します",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "name", "キャラ設定の名前")
+                        .max_length(NAME_MAX_LENGTH)
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "prompt",
+                        "キャラ設定の内容。必ず「あなたは～です。」と書いてください。",
+                    )
+                    .max_length(PROMPT_MAX_LENGTH)
+                    .required(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "登録されているキャラ設定の一覧を表示します",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "delete",
+                    "キャラ設定を削除します",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "name", "キャラ設定の名前")
+                        .set_autocomplete(true)
+                        .required(true),
+                ),
+            )
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.gemini.is_some()
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Err(CommandError::InvalidInput(
+                "キャラ設定はサーバー内でのみ使えます。".to_string(),
+            ));
+        };
+        let guild_id = guild_id.get();
+
+        let options = interaction.data.options();
+        let Some(ResolvedOption {
+            name: subcommand,
+            value: ResolvedValue::SubCommand(sub_options),
+            ..
+        }) = options.first()
+        else {
+            return Err(CommandError::InvalidInput(
+                "サブコマンドを指定してください。".to_string(),
+            ));
+        };
+
+        match *subcommand {
+            "create" => self.create(ctx, interaction, guild_id, sub_options).await,
+            "list" => self.list(ctx, interaction, guild_id).await,
+            "delete" => self.delete(ctx, interaction, guild_id, sub_options).await,
+            _ => Err(CommandError::InvalidInput(format!(
+                "不明なサブコマンドです: {}",
+                subcommand
+            ))),
+        }
+    }
+
+    async fn autocomplete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(focused) = interaction.data.autocomplete() else {
+            return Ok(());
+        };
+        respond_preset_names(ctx, interaction, &self.presets, focused.value).await
+    }
+}
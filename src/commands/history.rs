@@ -0,0 +1,47 @@
+use serenity::builder::GetMessages;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// Discord API で一度に取得できるメッセージ数
+const PAGE_SIZE: u8 = 100;
+
+/// チャンネルのメッセージを新しい順に最大 `limit` 件取得する
+///
+/// `since` を指定した場合は、それより前に投稿されたメッセージに達した時点で打ち切る。
+pub async fn fetch_history(
+    ctx: &Context,
+    channel_id: ChannelId,
+    limit: usize,
+    since: Option<Timestamp>,
+) -> Result<Vec<Message>, serenity::Error> {
+    let mut history = Vec::new();
+    let mut last_message_id: Option<MessageId> = None;
+
+    // Discord API limits to 100 messages per request, so we need to paginate
+    while history.len() < limit {
+        let mut messages_request = GetMessages::new().limit(PAGE_SIZE);
+
+        if let Some(last_id) = last_message_id {
+            messages_request = messages_request.before(last_id);
+        }
+
+        let messages = channel_id.messages(&ctx.http, messages_request).await?;
+        let page_len = messages.len();
+        last_message_id = messages.last().map(|m| m.id);
+
+        for message in messages {
+            if since.is_some_and(|since| message.timestamp < since) {
+                return Ok(history);
+            }
+            history.push(message);
+        }
+
+        // If we got fewer than 100 messages, we've reached the end
+        if page_len < PAGE_SIZE as usize {
+            break;
+        }
+    }
+
+    history.truncate(limit);
+    Ok(history)
+}
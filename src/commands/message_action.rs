@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serenity::builder::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use super::paginator::Paginator;
+use super::{CommandError, SlashCommand};
+use crate::config::Config;
+use nidaime_takohachi::llm::{LlmProvider, LlmRequest};
+use nidaime_takohachi::rate_limit::RateLimiter;
+
+/// メッセージの右クリックメニューから実行できる操作
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    TranslateToJapanese,
+    TranslateToEnglish,
+    Explain,
+}
+
+impl Action {
+    /// メニューに表示される名前
+    fn name(self) -> &'static str {
+        match self {
+            Action::TranslateToJapanese => "日本語に翻訳",
+            Action::TranslateToEnglish => "Translate to English",
+            Action::Explain => "このメッセージを解説",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Action::TranslateToJapanese => "🌐 日本語訳",
+            Action::TranslateToEnglish => "🌐 English translation",
+            Action::Explain => "💡 解説",
+        }
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Action::TranslateToJapanese => {
+                "与えられた Discord のメッセージを自然な日本語に翻訳してください。\
+                 翻訳文だけを出力し、説明や前置きは付けないでください。\
+                 Markdown の書式、URL、メンション、絵文字はそのまま残してください。"
+            }
+            Action::TranslateToEnglish => {
+                "Translate the given Discord message into natural English. \
+                 Output only the translation without any explanation or preface. \
+                 Keep Markdown formatting, URLs, mentions and emoji as they are."
+            }
+            Action::Explain => {
+                "あなたは Discord のチャットコミュニティのみんなに愛される Bot です。\
+                 与えられたメッセージの意味や背景、含まれる専門用語やスラングを日本語で分かりやすく解説してください。\
+                 前置きは不要です。"
+            }
+        }
+    }
+}
+
+/// メッセージを右クリックして LLM に翻訳や解説をさせるコマンド
+///
+/// 結果は実行した人にだけ見えるように返す。
+pub struct MessageAction {
+    action: Action,
+    llm: Option<Arc<dyn LlmProvider>>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl MessageAction {
+    pub fn new(
+        action: Action,
+        llm: Option<Arc<dyn LlmProvider>>,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            action,
+            llm,
+            rate_limiter,
+        }
+    }
+}
+
+#[async_trait]
+impl SlashCommand for MessageAction {
+    fn name(&self) -> &'static str {
+        self.action.name()
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+    }
+
+    fn kind(&self) -> CommandType {
+        CommandType::Message
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.gemini.is_some()
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(llm) = &self.llm else {
+            return Err(CommandError::InvalidInput(
+                "AI の設定がありません。".to_string(),
+            ));
+        };
+        let Some(ResolvedTarget::Message(message)) = interaction.data.target() else {
+            return Err(CommandError::InvalidInput(
+                "メッセージを選択してください。".to_string(),
+            ));
+        };
+        if message.content.trim().is_empty() {
+            return Err(CommandError::InvalidInput(
+                "このメッセージには本文がありません。".to_string(),
+            ));
+        }
+
+        let reservation = self
+            .rate_limiter
+            .check(interaction.user.id.get())
+            .map_err(CommandError::RateLimited)?;
+
+        interaction.defer_ephemeral(&ctx.http).await?;
+
+        let request =
+            LlmRequest::from_text(message.content.clone()).with_system(self.action.instruction());
+        let answer = llm
+            .generate(&request)
+            .await
+            .map_err(|e| CommandError::upstream(llm.service_name(), e))?;
+        reservation.commit();
+
+        let link = message.id.link(message.channel_id, interaction.guild_id);
+        let footer = format!(" Model: {}", llm.model());
+        let paginator = Paginator::from_text(self.action.title(), &answer.text, Colour::DARK_GREEN)
+            .with_footer(&footer)
+            .with_url(&link);
+        let sent = paginator.send(ctx, interaction).await?;
+        paginator.wait(ctx, interaction, &sent).await?;
+
+        Ok(())
+    }
+}
@@ -1,8 +1,265 @@
+pub mod ai_usage;
 pub mod count;
 pub mod debug_stock;
 pub mod debug_weather;
 pub mod dic;
+pub mod error;
 pub mod gemini;
+pub mod gemini_preset;
+pub mod history;
 pub mod marimo;
+pub mod message_action;
 pub mod modal;
+pub mod paginator;
 pub mod ping;
+pub mod summarize;
+pub mod weather;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serenity::builder::{
+    CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use serenity::model::application::{
+    CommandInteraction, CommandType, ResolvedOption, ResolvedValue,
+};
+use serenity::model::channel::Message;
+use serenity::prelude::*;
+
+use crate::config::{Config, GeminiConfig};
+pub use error::CommandError;
+use nidaime_takohachi::rate_limit::{RateLimitSettings, RateLimiter};
+
+/// スラッシュコマンドのトレイト
+#[async_trait]
+pub trait SlashCommand: Send + Sync {
+    /// コマンド名を返す（`register()` で指定する名前と一致させる）
+    fn name(&self) -> &'static str;
+
+    /// Discord に登録するコマンド定義を返す
+    fn register(&self) -> CreateCommand;
+
+    /// コマンドの種類を返す（メッセージの右クリックメニューに出すコマンドは `CommandType::Message`）
+    fn kind(&self) -> CommandType {
+        CommandType::ChatInput
+    }
+
+    /// 設定に応じてコマンドを有効にするかどうかを返す
+    fn is_enabled(&self, _config: &Config) -> bool {
+        true
+    }
+
+    /// コマンドの実行処理
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError>;
+
+    /// オプションの入力補完の要求に応答する（`set_autocomplete(true)` にしたオプションがある場合）
+    async fn autocomplete(
+        &self,
+        _ctx: &Context,
+        _interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    /// コマンドが必要とする Gateway Intents を返す（`on_message` を使う場合など）
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::empty()
+    }
+
+    /// 通常のメッセージを受け取ったときの処理（スレッドでの会話の続きなど）
+    async fn on_message(&self, _ctx: &Context, _message: &Message) -> Result<(), CommandError> {
+        Ok(())
+    }
+}
+
+/// 有効なスラッシュコマンドの一覧を保持し、登録と実行の振り分けを行う
+pub struct CommandRegistry {
+    commands: Vec<Box<dyn SlashCommand>>,
+}
+
+impl CommandRegistry {
+    /// 設定に基づいて有効なコマンドだけを集めたレジストリを作成する
+    ///
+    /// 新しいコマンドを追加した場合はここに追加する。
+    pub fn new(config: &Config) -> Self {
+        let llm = config.gemini.as_ref().map(GeminiConfig::provider);
+        let presets = Arc::new(gemini_preset::load_presets(config));
+        let rate_limiter = Arc::new(
+            config
+                .gemini
+                .as_ref()
+                .map(GeminiConfig::rate_limiter)
+                .unwrap_or_else(|| RateLimiter::new(RateLimitSettings::default())),
+        );
+
+        let all_commands: Vec<Box<dyn SlashCommand>> = vec![
+            Box::new(ping::Ping),
+            Box::new(modal::Modal),
+            Box::new(count::Count),
+            Box::new(marimo::Marimo),
+            Box::new(dic::Dic::new(config)),
+            Box::new(gemini::Gemini::new(
+                config,
+                llm.clone(),
+                presets.clone(),
+                rate_limiter.clone(),
+            )),
+            Box::new(summarize::Summarize::new(llm.clone(), rate_limiter.clone())),
+            Box::new(message_action::MessageAction::new(
+                message_action::Action::TranslateToJapanese,
+                llm.clone(),
+                rate_limiter.clone(),
+            )),
+            Box::new(message_action::MessageAction::new(
+                message_action::Action::TranslateToEnglish,
+                llm.clone(),
+                rate_limiter.clone(),
+            )),
+            Box::new(message_action::MessageAction::new(
+                message_action::Action::Explain,
+                llm,
+                rate_limiter.clone(),
+            )),
+            Box::new(gemini_preset::GeminiPreset::new(presets)),
+            Box::new(ai_usage::AiUsage::new(rate_limiter)),
+            Box::new(weather::Weather::new(config)),
+            Box::new(debug_weather::DebugWeather),
+            Box::new(debug_stock::DebugStock),
+        ];
+
+        let commands = all_commands
+            .into_iter()
+            .filter(|command| command.is_enabled(config))
+            .collect();
+
+        Self { commands }
+    }
+
+    /// Discord に登録するコマンド定義の一覧を返す
+    pub fn create_commands(&self) -> Vec<CreateCommand> {
+        self.commands
+            .iter()
+            .map(|command| command.register().kind(command.kind()))
+            .collect()
+    }
+
+    /// 有効なコマンドが必要とする Gateway Intents をまとめて返す
+    pub fn intents(&self) -> GatewayIntents {
+        self.commands
+            .iter()
+            .fold(GatewayIntents::empty(), |intents, command| {
+                intents | command.intents()
+            })
+    }
+
+    /// 受け取ったメッセージを各コマンドに渡す
+    pub async fn dispatch_message(&self, ctx: &Context, message: &Message) {
+        for command in &self.commands {
+            if let Err(why) = command.on_message(ctx, message).await {
+                eprintln!(
+                    "[/{}] Failed to handle message {}: {}",
+                    command.name(),
+                    message.id,
+                    why
+                );
+            }
+        }
+    }
+
+    /// コマンド名と種類から該当するコマンドを探す
+    ///
+    /// スラッシュコマンドと右クリックメニューのコマンドは同じ名前を持てるので、種類でも区別する。
+    pub fn find(&self, name: &str, kind: CommandType) -> Option<&dyn SlashCommand> {
+        self.commands
+            .iter()
+            .find(|command| command.name() == name && command.kind() == kind)
+            .map(|command| command.as_ref())
+    }
+}
+
+/// サブコマンドのオプションから文字列の値を取り出す（なければ空文字列）
+pub fn string_option<'a>(options: &'a [ResolvedOption<'_>], name: &str) -> &'a str {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match opt.value {
+            ResolvedValue::String(s) => Some(s),
+            _ => None,
+        })
+        .unwrap_or("")
+}
+
+/// メッセージで応答する
+pub async fn respond(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    content: String,
+    ephemeral: bool,
+) -> Result<(), CommandError> {
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(ephemeral),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> Config {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn registry_skips_commands_without_config() {
+        let registry = CommandRegistry::new(&config(r#"{"discord_token": "x", "guild_id": 1}"#));
+
+        assert!(registry.find("ping", CommandType::ChatInput).is_some());
+        assert!(registry.find("gemini", CommandType::ChatInput).is_none());
+        assert!(registry.find("dic", CommandType::ChatInput).is_none());
+    }
+
+    #[test]
+    fn find_distinguishes_command_kind() {
+        let registry = CommandRegistry::new(&config(r#"{"discord_token": "x", "guild_id": 1}"#));
+
+        assert!(registry.find("ping", CommandType::Message).is_none());
+        assert!(registry.find("unknown", CommandType::ChatInput).is_none());
+    }
+
+    #[test]
+    fn intents_are_empty_without_message_handlers() {
+        let registry = CommandRegistry::new(&config(r#"{"discord_token": "x", "guild_id": 1}"#));
+
+        assert_eq!(registry.intents(), GatewayIntents::empty());
+    }
+
+    #[test]
+    fn gemini_requests_message_intents_only_for_thread_conversations() {
+        let registry = CommandRegistry::new(&config(
+            r#"{"discord_token": "x", "guild_id": 1, "gemini": {"api_key": "k"}}"#,
+        ));
+        assert!(!registry.intents().contains(GatewayIntents::GUILD_MESSAGES));
+
+        let registry = CommandRegistry::new(&config(
+            r#"{"discord_token": "x", "guild_id": 1, "gemini": {"api_key": "k", "thread_conversations": true}}"#,
+        ));
+        assert!(
+            registry
+                .intents()
+                .contains(GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT)
+        );
+    }
+}
@@ -1,31 +1,52 @@
+use async_trait::async_trait;
 use serenity::builder::*;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 use serenity::utils::CreateQuickModal;
 
-pub async fn run(ctx: &Context, interaction: &CommandInteraction) -> Result<(), serenity::Error> {
-    let modal = CreateQuickModal::new("About you")
-        .timeout(std::time::Duration::from_secs(600))
-        .short_field("First name")
-        .short_field("Last name")
-        .paragraph_field("Hobbies and interests");
-    let response = interaction.quick_modal(ctx, modal).await?.unwrap();
+use super::{CommandError, SlashCommand};
 
-    let inputs = response.inputs;
-    let (first_name, last_name, hobbies) = (&inputs[0], &inputs[1], &inputs[2]);
+pub struct Modal;
 
-    response
-        .interaction
-        .create_response(
-            ctx,
-            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(
-                format!("**Name**: {first_name} {last_name}\n\nHobbies and interests: {hobbies}"),
-            )),
-        )
-        .await?;
-    Ok(())
-}
+#[async_trait]
+impl SlashCommand for Modal {
+    fn name(&self) -> &'static str {
+        "modal"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name()).description("Asks some details about you")
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let modal = CreateQuickModal::new("About you")
+            .timeout(std::time::Duration::from_secs(600))
+            .short_field("First name")
+            .short_field("Last name")
+            .paragraph_field("Hobbies and interests");
+        let response = interaction
+            .quick_modal(ctx, modal)
+            .await?
+            .ok_or(CommandError::Timeout("モーダルの入力"))?;
+
+        let inputs = response.inputs;
+        let (first_name, last_name, hobbies) = (&inputs[0], &inputs[1], &inputs[2]);
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("modal").description("Asks some details about you")
+        response
+            .interaction
+            .create_response(
+                ctx,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content(format!(
+                        "**Name**: {first_name} {last_name}\n\nHobbies and interests: {hobbies}"
+                    )),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
 }
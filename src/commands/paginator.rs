@@ -0,0 +1,347 @@
+use std::time::Duration;
+
+use serenity::builder::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// Discord の埋め込みの文字数上限
+pub const EMBED_TITLE_LIMIT: usize = 256;
+pub const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+pub const EMBED_FOOTER_LIMIT: usize = 2048;
+
+/// コードブロックを閉じる「\n```」の文字数
+const CLOSING_FENCE_LEN: usize = 4;
+/// 開き直す行がこれより長いコードブロック（言語名などが長いもの）は「```」だけで開き直す
+const MAX_REOPEN_FENCE_LEN: usize = 64;
+
+const PREV_BUTTON_ID: &str = "paginator:prev";
+const NEXT_BUTTON_ID: &str = "paginator:next";
+const PAGINATOR_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// 上限を超える文字列を切り詰め、末尾に「…」を付ける
+pub fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(limit.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Markdown のテキストを上限文字数ごとのページに分割する
+///
+/// 行単位で分割し、コードブロックの途中で分割する場合はページ末尾で閉じて次のページで開き直す。
+/// 1 行が上限を超える場合だけ行の途中で分割する。
+pub fn split_markdown(text: &str, limit: usize) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    let mut open_fence: Option<String> = None;
+
+    for line in text.lines() {
+        // コードブロックの途中なら、開き直す行と閉じる行の分を空けておく
+        let reserve = match &open_fence {
+            Some(fence) => fence.chars().count() + 1 + CLOSING_FENCE_LEN,
+            None => 0,
+        };
+        let max_piece = limit.saturating_sub(reserve).max(1);
+
+        for piece in split_chars(line, max_piece) {
+            let piece_len = piece.chars().count();
+            let closing_len = if open_fence.is_some() {
+                CLOSING_FENCE_LEN
+            } else {
+                0
+            };
+
+            if current_len > 0 && current_len + 1 + piece_len + closing_len > limit {
+                if open_fence.is_some() {
+                    current.push_str("\n```");
+                }
+                pages.push(std::mem::take(&mut current));
+                current_len = 0;
+                if let Some(fence) = &open_fence {
+                    current.push_str(fence);
+                    current_len = fence.chars().count();
+                }
+            }
+
+            if current_len > 0 {
+                current.push('\n');
+                current_len += 1;
+            }
+            current.push_str(&piece);
+            current_len += piece_len;
+        }
+
+        if line.trim_start().starts_with("```") {
+            open_fence = match open_fence {
+                Some(_) => None,
+                None => Some(reopen_fence(line, limit)),
+            };
+        }
+    }
+
+    if !current.trim().is_empty() || pages.is_empty() {
+        pages.push(current);
+    }
+
+    pages
+}
+
+/// 次のページでコードブロックを開き直す行を返す
+///
+/// 元の行が長すぎる場合は、ページに本文を入れる余地を残すため「```」だけにする。
+fn reopen_fence(line: &str, limit: usize) -> String {
+    let fence = line.trim();
+    let len = fence.chars().count();
+    if len > MAX_REOPEN_FENCE_LEN || len * 4 > limit {
+        "```".to_string()
+    } else {
+        fence.to_string()
+    }
+}
+
+/// 文字列を最大 `max` 文字ずつに分割する
+fn split_chars(line: &str, max: usize) -> Vec<String> {
+    if line.is_empty() {
+        return vec![String::new()];
+    }
+    let chars: Vec<char> = line.chars().collect();
+    chars.chunks(max).map(|c| c.iter().collect()).collect()
+}
+
+/// 「前へ」「続きを見る」ボタンで埋め込みのページを切り替える
+pub struct Paginator {
+    pages: Vec<CreateEmbed>,
+    index: usize,
+}
+
+impl Paginator {
+    pub fn new(pages: Vec<CreateEmbed>) -> Self {
+        Self { pages, index: 0 }
+    }
+
+    /// テキストを埋め込みの上限ごとに分割し、同じタイトルと色のページにする
+    ///
+    /// ページが複数ある場合はフッターに「📄 i/n」を表示する。
+    pub fn from_text(title: &str, text: &str, colour: Colour) -> Self {
+        let title = truncate(title, EMBED_TITLE_LIMIT);
+        let pages = split_markdown(text, EMBED_DESCRIPTION_LIMIT)
+            .into_iter()
+            .map(|page| {
+                CreateEmbed::new()
+                    .title(&title)
+                    .description(page)
+                    .colour(colour)
+            })
+            .collect();
+        Self::new(pages).with_footer("")
+    }
+
+    /// 各ページのフッターを設定する（ページが複数ある場合は後ろに「📄 i/n」を付ける）
+    pub fn with_footer(mut self, footer: &str) -> Self {
+        let page_count = self.pages.len();
+        self.pages = std::mem::take(&mut self.pages)
+            .into_iter()
+            .enumerate()
+            .map(|(i, page)| {
+                let footer = match (page_count > 1, footer.is_empty()) {
+                    (false, true) => return page,
+                    (false, false) => footer.to_string(),
+                    (true, true) => format!("📄 {}/{}", i + 1, page_count),
+                    (true, false) => format!("{}\n📄 {}/{}", footer, i + 1, page_count),
+                };
+                page.footer(CreateEmbedFooter::new(truncate(
+                    &footer,
+                    EMBED_FOOTER_LIMIT,
+                )))
+            })
+            .collect();
+        self
+    }
+
+    /// 各ページのタイトルのリンク先を設定する
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.pages = std::mem::take(&mut self.pages)
+            .into_iter()
+            .map(|page| page.url(url))
+            .collect();
+        self
+    }
+
+    /// 最初に表示するページを指定する（0 始まり。範囲外なら最後のページ）
+    pub fn with_start_page(mut self, index: usize) -> Self {
+        self.index = index.min(self.pages.len().saturating_sub(1));
+        self
+    }
+
+    fn current_page(&self) -> CreateEmbed {
+        self.pages[self.index].clone()
+    }
+
+    fn buttons(&self) -> Vec<CreateActionRow> {
+        if self.pages.len() <= 1 {
+            return vec![];
+        }
+        vec![CreateActionRow::Buttons(vec![
+            CreateButton::new(PREV_BUTTON_ID)
+                .label("◀ 前へ")
+                .style(ButtonStyle::Secondary)
+                .disabled(self.index == 0),
+            CreateButton::new(NEXT_BUTTON_ID)
+                .label(format!(
+                    "続きを見る ({}/{}) ▶",
+                    self.index + 1,
+                    self.pages.len()
+                ))
+                .style(ButtonStyle::Primary)
+                .disabled(self.index + 1 >= self.pages.len()),
+        ])]
+    }
+
+    /// 最初のページで defer 済みの応答を置き換え、送信したメッセージを返す
+    pub async fn send(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<Message, serenity::Error> {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .embed(self.current_page())
+                    .components(self.buttons()),
+            )
+            .await
+    }
+
+    /// ボタンが押されるたびにページを切り替える
+    ///
+    /// 一定時間操作がなければボタンを消して終了する。ページが 1 つだけなら何もしない。
+    pub async fn wait(
+        mut self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        message: &Message,
+    ) -> Result<(), serenity::Error> {
+        if self.pages.len() <= 1 {
+            return Ok(());
+        }
+
+        // ページを切り替えられるのはコマンドを実行した人だけ
+        while let Some(press) = message
+            .await_component_interaction(&ctx.shard)
+            .author_id(interaction.user.id)
+            .timeout(PAGINATOR_TIMEOUT)
+            .await
+        {
+            match press.data.custom_id.as_str() {
+                PREV_BUTTON_ID => self.index = self.index.saturating_sub(1),
+                NEXT_BUTTON_ID => self.index = (self.index + 1).min(self.pages.len() - 1),
+                _ => continue,
+            }
+
+            press
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .embed(self.current_page())
+                            .components(self.buttons()),
+                    ),
+                )
+                .await?;
+        }
+
+        interaction
+            .edit_response(&ctx.http, EditInteractionResponse::new().components(vec![]))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_a_single_page() {
+        assert_eq!(split_markdown("hello\nworld", 100), vec!["hello\nworld"]);
+    }
+
+    #[test]
+    fn empty_text_is_a_single_empty_page() {
+        assert_eq!(split_markdown("", 100), vec![""]);
+    }
+
+    #[test]
+    fn splits_between_lines() {
+        let text = ["a".repeat(30), "b".repeat(30), "c".repeat(30)].join("\n");
+        let pages = split_markdown(&text, 64);
+
+        assert_eq!(
+            pages,
+            vec![
+                format!("{}\n{}", "a".repeat(30), "b".repeat(30)),
+                "c".repeat(30)
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_long_line() {
+        let pages = split_markdown(&"x".repeat(250), 100);
+
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|page| page.chars().count() <= 100));
+        assert_eq!(pages.concat(), "x".repeat(250));
+    }
+
+    #[test]
+    fn closes_and_reopens_code_block() {
+        let mut lines = vec!["```rust".to_string()];
+        lines.extend((0..10).map(|i| format!("let x{i} = {i};")));
+        lines.push("```".to_string());
+        let pages = split_markdown(&lines.join("\n"), 60);
+
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert!(page.chars().count() <= 60, "{page:?}");
+            assert_eq!(page.matches("```").count() % 2, 0, "{page:?}");
+        }
+        assert!(pages[1].starts_with("```rust\n"));
+    }
+
+    #[test]
+    fn long_fence_line_stays_within_limit() {
+        let fence = format!("```{}", "l".repeat(40));
+        let text = format!("{}\n{}\n```", fence, "x".repeat(190));
+        let pages = split_markdown(&text, 200);
+
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert!(page.chars().count() <= 200, "{page:?}");
+        }
+        assert!(pages[1].starts_with(&fence));
+    }
+
+    #[test]
+    fn very_long_fence_line_is_reopened_without_language() {
+        let fence = format!("```{}", "l".repeat(100));
+        let text = format!("{}\n{}\n```", fence, "x".repeat(300));
+        let pages = split_markdown(&text, 200);
+
+        for page in &pages {
+            assert!(page.chars().count() <= 200, "{page:?}");
+        }
+        assert!(pages[1].starts_with("```\n"));
+    }
+
+    #[test]
+    fn truncate_adds_ellipsis() {
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("abc", 4), "abc");
+    }
+}
@@ -1,10 +1,33 @@
-use serenity::builder::CreateCommand;
-use serenity::model::application::ResolvedOption;
+use async_trait::async_trait;
+use serenity::builder::{
+    CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use serenity::model::application::CommandInteraction;
+use serenity::prelude::*;
 
-pub fn run(_options: &[ResolvedOption]) -> String {
-    ":ping_pong: pong !".to_string()
-}
+use super::{CommandError, SlashCommand};
+
+pub struct Ping;
+
+#[async_trait]
+impl SlashCommand for Ping {
+    fn name(&self) -> &'static str {
+        "ping"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name()).description("A ping command")
+    }
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("ping").description("A ping command")
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let data = CreateInteractionResponseMessage::new().content(":ping_pong: pong !");
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await?;
+        Ok(())
+    }
 }
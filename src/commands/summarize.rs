@@ -0,0 +1,366 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono_tz::Asia::Tokyo;
+use regex::{Captures, Regex};
+use serenity::builder::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use super::history::fetch_history;
+use super::paginator::Paginator;
+use super::{CommandError, SlashCommand};
+use crate::config::Config;
+use nidaime_takohachi::llm::{LlmProvider, LlmRequest, Usage};
+use nidaime_takohachi::rate_limit::RateLimiter;
+
+const DEFAULT_COUNT: i64 = 100;
+const MAX_COUNT: i64 = 1000;
+/// `since` で指定できる期間の上限（日数）
+const MAX_SINCE_DAYS: u64 = 365;
+/// 1 回のリクエストに含める会話ログの文字数の目安（超える場合は分割して要約してからまとめる）
+const CHUNK_CHARS: usize = 12000;
+/// 1 件のメッセージとして渡す文字数の上限
+const MESSAGE_CHARS: usize = 500;
+
+const MAP_INSTRUCTION: &str = "あなたは Discord のチャンネルの会話を要約するアシスタントです。\
+与えられた会話ログを日本語で簡潔に要約してください。\
+話題ごとに箇条書きにし、誰が何を言ったかが分かるようにしてください。\
+各行の先頭の [番号] はメッセージの番号です。重要なメッセージには要約の中で [番号] の形で番号を付けてください。\
+前置きやあいさつは不要です。";
+
+const REDUCE_INSTRUCTION: &str = "あなたは Discord のチャンネルの会話を要約するアシスタントです。\
+会話ログを区切りごとに要約したものが与えられるので、全体を 1 つの簡潔な日本語の要約にまとめてください。\
+話題ごとに箇条書きにし、要約に含まれる [番号] はそのまま残してください。\
+前置きやあいさつは不要です。";
+
+pub struct Summarize {
+    llm: Option<Arc<dyn LlmProvider>>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl Summarize {
+    pub fn new(llm: Option<Arc<dyn LlmProvider>>, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self { llm, rate_limiter }
+    }
+}
+
+#[async_trait]
+impl SlashCommand for Summarize {
+    fn name(&self) -> &'static str {
+        "summarize"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("チャンネルの最近の会話を要約します")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "count",
+                    "要約するメッセージの数（デフォルト: 100）",
+                )
+                .min_int_value(1)
+                .max_int_value(MAX_COUNT as u64)
+                .required(false),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "since",
+                    "どれくらい前からの会話を要約するか（例: 30m, 3h, 1d）",
+                )
+                .required(false),
+            )
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.gemini.is_some()
+    }
+
+    fn intents(&self) -> GatewayIntents {
+        // 取得したメッセージの本文を読むため（履歴は HTTP で取得するので GUILD_MESSAGES は不要）
+        GatewayIntents::MESSAGE_CONTENT
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(llm) = &self.llm else {
+            return Err(CommandError::InvalidInput(
+                "AI の設定がありません。".to_string(),
+            ));
+        };
+
+        let since_option = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "since")
+            .and_then(|opt| opt.value.as_str());
+        let since = match since_option {
+            Some(text) => Some(since_timestamp(text).map_err(CommandError::InvalidInput)?),
+            None => None,
+        };
+        // 期間だけが指定された場合はその期間のメッセージをできるだけ多く読む
+        let count = interaction
+            .data
+            .options
+            .iter()
+            .find(|opt| opt.name == "count")
+            .and_then(|opt| opt.value.as_i64())
+            .unwrap_or(if since.is_some() {
+                MAX_COUNT
+            } else {
+                DEFAULT_COUNT
+            }) as usize;
+
+        // 制限を超えた場合はエフェメラルで返したいので defer より前に確認する
+        let reservation = self
+            .rate_limiter
+            .check(interaction.user.id.get())
+            .map_err(CommandError::RateLimited)?;
+
+        interaction.defer(&ctx.http).await?;
+
+        let mut history = fetch_history(ctx, interaction.channel_id, count, since).await?;
+        history.retain(|m| !m.content.trim().is_empty());
+        // 古い順に並べ直す
+        history.reverse();
+
+        if history.is_empty() {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("要約するメッセージがありません。"),
+                )
+                .await?;
+            return Ok(());
+        }
+
+        let lines: Vec<String> = history
+            .iter()
+            .enumerate()
+            .map(|(i, m)| format_line(i + 1, m))
+            .collect();
+        let (summary, usage) = summarize(llm.as_ref(), &lines).await?;
+        reservation.commit();
+        let summary = link_references(&summary, &history, interaction.guild_id);
+
+        let first = history.first().map(format_time).unwrap_or_default();
+        let last = history.last().map(format_time).unwrap_or_default();
+        let mut footer = format!(
+            " Model: {}\n💬 {}件 ({} 〜 {})",
+            llm.model(),
+            history.len(),
+            first,
+            last
+        );
+        if let Some(usage) = usage {
+            footer.push_str(&format!("\n📊 Tokens: {}", usage.total_tokens));
+        }
+
+        let paginator = Paginator::from_text("📝 最近の会話の要約", &summary, Colour::DARK_GREEN)
+            .with_footer(&footer);
+        let message = paginator.send(ctx, interaction).await?;
+        paginator.wait(ctx, interaction, &message).await?;
+
+        Ok(())
+    }
+}
+
+/// 会話ログを要約する
+///
+/// 長い場合は一定の長さごとに要約し（map）、それらを 1 つにまとめる（reduce）。
+async fn summarize(
+    llm: &dyn LlmProvider,
+    lines: &[String],
+) -> Result<(String, Option<Usage>), CommandError> {
+    let chunks = chunk_lines(lines, CHUNK_CHARS);
+    let mut usage = None;
+
+    let mut partials = Vec::with_capacity(chunks.len());
+    for chunk in &chunks {
+        let request = LlmRequest::from_text(chunk).with_system(MAP_INSTRUCTION);
+        let answer = llm
+            .generate(&request)
+            .await
+            .map_err(|e| CommandError::upstream(llm.service_name(), e))?;
+        add_usage(&mut usage, answer.usage);
+        partials.push(answer.text);
+    }
+
+    if partials.len() == 1 {
+        return Ok((partials.remove(0), usage));
+    }
+
+    let combined = partials
+        .iter()
+        .enumerate()
+        .map(|(i, text)| format!("## 区切り {}\n{}", i + 1, text))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let request = LlmRequest::from_text(combined).with_system(REDUCE_INSTRUCTION);
+    let answer = llm
+        .generate(&request)
+        .await
+        .map_err(|e| CommandError::upstream(llm.service_name(), e))?;
+    add_usage(&mut usage, answer.usage);
+
+    Ok((answer.text, usage))
+}
+
+fn add_usage(total: &mut Option<Usage>, usage: Option<Usage>) {
+    let Some(usage) = usage else {
+        return;
+    };
+    let total = total.get_or_insert_with(Usage::default);
+    total.prompt_tokens += usage.prompt_tokens;
+    total.completion_tokens += usage.completion_tokens;
+    total.total_tokens += usage.total_tokens;
+}
+
+/// 行を `limit` 文字程度ずつにまとめる
+fn chunk_lines(lines: &[String], limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        if !current.is_empty() && current.chars().count() + line.chars().count() > limit {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// `[番号] 時刻 名前: 本文` の形で 1 行にする
+fn format_line(number: usize, message: &Message) -> String {
+    let author = message
+        .author
+        .global_name
+        .as_deref()
+        .unwrap_or(&message.author.name);
+    let content: String = message
+        .content
+        .replace('\n', " ")
+        .chars()
+        .take(MESSAGE_CHARS)
+        .collect();
+    format!(
+        "[{}] {} {}: {}",
+        number,
+        format_time(message),
+        author,
+        content
+    )
+}
+
+fn format_time(message: &Message) -> String {
+    message
+        .timestamp
+        .with_timezone(&Tokyo)
+        .format("%m/%d %H:%M")
+        .to_string()
+}
+
+/// 要約中の `[番号]` を元のメッセージへのリンクにする
+fn link_references(summary: &str, history: &[Message], guild_id: Option<GuildId>) -> String {
+    let re = Regex::new(r"\[(\d+)\]").unwrap();
+    re.replace_all(summary, |caps: &Captures| {
+        let message = caps[1]
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| history.get(i));
+        match message {
+            Some(m) => format!("[[{}]]({})", &caps[1], m.id.link(m.channel_id, guild_id)),
+            None => caps[0].to_string(),
+        }
+    })
+    .into_owned()
+}
+
+/// `since` の指定から、どの時刻以降のメッセージを要約するかを求める（エラーはユーザーに表示するメッセージ）
+fn since_timestamp(text: &str) -> Result<Timestamp, String> {
+    let since = parse_since(text).ok_or_else(|| {
+        format!(
+            "「{}」は期間として解釈できません。30m, 3h, 1d のように指定してください。",
+            text
+        )
+    })?;
+    let out_of_range = || format!("期間は {} 日以内で指定してください。", MAX_SINCE_DAYS);
+    if since > Duration::from_secs(MAX_SINCE_DAYS * 24 * 60 * 60) {
+        return Err(out_of_range());
+    }
+    chrono::Duration::from_std(since)
+        .ok()
+        .and_then(|since| chrono::Utc::now().checked_sub_signed(since))
+        .map(Timestamp::from)
+        .ok_or_else(out_of_range)
+}
+
+/// `30m`, `3h`, `1d` のような期間を解釈する
+///
+/// 数値が大きすぎて秒数に直せない場合も `None` を返す。
+fn parse_since(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let unit_start = text.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = text.split_at(unit_start);
+    let value: u64 = value.parse().ok()?;
+    let seconds: u64 = match unit.trim() {
+        "m" | "min" | "分" => 60,
+        "h" | "時間" => 60 * 60,
+        "d" | "日" => 24 * 60 * 60,
+        _ => return None,
+    };
+    if value == 0 {
+        return None;
+    }
+    value.checked_mul(seconds).map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_since("30m"), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_since("3h"), Some(Duration::from_secs(3 * 60 * 60)));
+        assert_eq!(parse_since("1d"), Some(Duration::from_secs(24 * 60 * 60)));
+        assert_eq!(
+            parse_since(" 2 時間 "),
+            Some(Duration::from_secs(2 * 60 * 60))
+        );
+        assert_eq!(parse_since("10分"), Some(Duration::from_secs(10 * 60)));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(parse_since(""), None);
+        assert_eq!(parse_since("30"), None);
+        assert_eq!(parse_since("0h"), None);
+        assert_eq!(parse_since("3w"), None);
+        assert_eq!(parse_since("h"), None);
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(parse_since("18446744073709551615d"), None);
+        assert_eq!(parse_since("99999999999999999999d"), None);
+    }
+
+    #[test]
+    fn since_timestamp_rejects_out_of_range() {
+        assert!(since_timestamp("100000000d").is_err());
+        assert!(since_timestamp("366d").is_err());
+        assert!(since_timestamp("365d").is_ok());
+    }
+}
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use serenity::builder::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use super::gemini_preset::AUTOCOMPLETE_LIMIT;
+use super::{CommandError, SlashCommand, string_option};
+use crate::config::{Config, WeatherConfig};
+use nidaime_takohachi::weather::{self, City, CityDirectory, DefaultCityStore};
+
+const DEFAULT_DEFAULTS_PATH: &str = "weather_defaults.json";
+/// 設定でも既定の地点を指定していないときの地点（東京）
+const FALLBACK_CITY_ID: &str = "130010";
+
+pub struct Weather {
+    config: WeatherConfig,
+    cities: CityDirectory,
+    defaults: DefaultCityStore,
+}
+
+impl Weather {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.weather.clone(),
+            cities: CityDirectory::new(),
+            defaults: load_defaults(config),
+        }
+    }
+
+    /// 入力された地点を探す。省略されていれば、ユーザーの既定の地点か設定の既定の地点を使う
+    async fn resolve_city(&self, query: &str, user_id: UserId) -> Result<City, CommandError> {
+        let query = if query.trim().is_empty() {
+            self.defaults
+                .get(user_id.get())
+                .or_else(|| self.config.default_city.clone())
+                .unwrap_or_else(|| FALLBACK_CITY_ID.to_string())
+        } else {
+            query.to_string()
+        };
+
+        self.cities.find(&query).await.ok_or_else(|| {
+            CommandError::InvalidInput(format!(
+                "地点「{}」が見つかりませんでした。入力候補から選んでください。",
+                query
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl SlashCommand for Weather {
+    fn name(&self) -> &'static str {
+        "weather"
+    }
+
+    fn register(&self) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description("今日・明日・明後日の天気予報を表示します")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "city",
+                    "地点の名前（省略すると既定の地点）",
+                )
+                .set_autocomplete(true)
+                .required(false),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "set_default",
+                    "この地点を自分の既定の地点にする",
+                )
+                .required(false),
+            )
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let options = interaction.data.options();
+        let query = string_option(&options, "city");
+        let set_default = options.iter().any(|opt| {
+            opt.name == "set_default" && matches!(opt.value, ResolvedValue::Boolean(true))
+        });
+
+        interaction.defer(&ctx.http).await?;
+
+        let city = self.resolve_city(query, interaction.user.id).await?;
+        if set_default {
+            self.defaults
+                .set(interaction.user.id.get(), &city.id)
+                .await
+                .map_err(|e| CommandError::Other(Box::new(e)))?;
+        }
+
+        let forecast = weather::fetch_forecast(&city.id)
+            .await
+            .map_err(|e| CommandError::upstream("天気予報 API", e))?;
+
+        let mut embed = CreateEmbed::new()
+            .title(&forecast.title)
+            .url(&forecast.link)
+            .colour(Colour::BLUE)
+            .footer(CreateEmbedFooter::new(format!(
+                "{} 発表",
+                forecast.publishing_office
+            )));
+        if !forecast.description.headline_text.is_empty() {
+            embed = embed.description(&forecast.description.headline_text);
+        }
+        if let Ok(timestamp) = Timestamp::parse(&forecast.public_time) {
+            embed = embed.timestamp(timestamp);
+        }
+        for day in &forecast.forecasts {
+            let name = match day.date() {
+                Some(date) => format!("{} ({})", day.date_label, date.format("%-m/%-d")),
+                None => day.date_label.clone(),
+            };
+            embed = embed.field(name, weather::render::day_field(day), true);
+        }
+
+        let mut response = EditInteractionResponse::new().embed(embed);
+        if set_default {
+            response = response.content(format!(
+                ":white_check_mark: 既定の地点を「{}」にしました。",
+                city.label()
+            ));
+        }
+        interaction.edit_response(&ctx.http, response).await?;
+        Ok(())
+    }
+
+    async fn autocomplete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandError> {
+        let Some(focused) = interaction.data.autocomplete() else {
+            return Ok(());
+        };
+        let choices = self
+            .cities
+            .search(focused.value, AUTOCOMPLETE_LIMIT)
+            .await
+            .into_iter()
+            .map(|city| AutocompleteChoice::new(city.label(), city.id))
+            .collect();
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Autocomplete(
+                    CreateAutocompleteResponse::new().set_choices(choices),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// 設定されたファイルからユーザーごとの既定の地点を読み込む
+fn load_defaults(config: &Config) -> DefaultCityStore {
+    let path = config
+        .weather
+        .defaults_path
+        .clone()
+        .unwrap_or_else(|| DEFAULT_DEFAULTS_PATH.to_string());
+    DefaultCityStore::load(&path).unwrap_or_else(|e| {
+        eprintln!("[/weather] Failed to load default cities from {path}, starting empty: {e}");
+        DefaultCityStore::new(&path)
+    })
+}
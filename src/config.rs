@@ -1,7 +1,16 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
+use nidaime_takohachi::gemini::GeminiClient;
+use nidaime_takohachi::google_auth;
+use nidaime_takohachi::google_sheets::{self, SheetsClient};
+use nidaime_takohachi::llm::{ConversationStore, LlmProvider, conversation};
+use nidaime_takohachi::openai::OpenAiClient;
+use nidaime_takohachi::rate_limit::{RateLimitSettings, RateLimiter};
+use nidaime_takohachi::trigger::{LocalTriggerStore, TriggerSheet, TriggerStore};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +23,8 @@ pub struct Config {
     pub debug_slash_commands: bool,
     pub dic: Option<DicConfig>,
     pub gemini: Option<GeminiConfig>,
+    #[serde(default)]
+    pub weather: WeatherConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -24,25 +35,284 @@ pub struct ScheduledTasksConfig {
     #[serde(default)]
     pub delete_message_channels: HashMap<u64, u64>,
     pub daily_morning_task: Option<DailyMorningTaskConfig>,
+    pub weather_warning_task: Option<WeatherWarningTaskConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyMorningTaskConfig {
     pub enabled: bool,
     pub channel_id: u64,
+    /// 雑学の生成に使う Gemini API のキー（省略時は `gemini` で設定した LLM を使う）
     pub gemini_api_key: Option<String>,
 }
 
+/// `/weather` の設定
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WeatherConfig {
+    /// 地点を省略し、既定の地点も設定していないときに表示する地点（地点名か地点コード。省略時は東京）
+    #[serde(default)]
+    pub default_city: Option<String>,
+    /// ユーザーごとの既定の地点を保存するファイル（省略時は weather_defaults.json）
+    #[serde(default)]
+    pub defaults_path: Option<String>,
+}
+
+/// 警報・注意報の発表・解除を投稿するタスクの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherWarningTaskConfig {
+    pub enabled: bool,
+    pub channel_id: u64,
+    /// 警報・注意報を見る区域
+    pub areas: Vec<WarningAreaConfig>,
+    /// 気象庁のデータを確認する間隔（秒、省略時は 600 秒）
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    /// 投稿済みの警報・注意報を保存するファイル（省略時は weather_warnings.json）
+    #[serde(default)]
+    pub state_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarningAreaConfig {
+    /// 府県予報区のコード（東京都なら 130000）
+    pub office_code: String,
+    /// 一次細分区域のコード（東京地方なら 130010）
+    pub area_code: String,
+    /// 投稿に表示する名前（省略時は区域のコード）
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeminiConfig {
-    pub api_key: String,
+    /// 使用する LLM のバックエンド（省略時は gemini）
+    #[serde(default)]
+    pub provider: LlmProviderKind,
+    /// API キー（gemini では必須。ローカルの OpenAI 互換サーバーでは省略できる）
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 使用するモデル（gemini では省略時は gemini-2.5-flash。openai_compatible では必須）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// API のベース URL（省略時は gemini なら Google の公開エンドポイント、
+    /// openai_compatible ならローカルの Ollama）
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// リクエストのタイムアウト秒数（省略時は 120 秒。ストリーミングではデータが届く間隔に対するタイムアウト）
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// `/gemini` の `thread` オプションで、回答から作ったスレッド内で会話を続けられるようにする
+    /// （スレッド内の発言を読むため、Developer Portal で Message Content Intent を有効にする必要がある）
+    #[serde(default)]
+    pub thread_conversations: bool,
+    /// スレッドでの会話履歴として保持するトークン数の目安（省略時は 8000）
+    #[serde(default)]
+    pub conversation_token_budget: Option<usize>,
+    /// 会話が破棄されるまでの無発言時間（分、省略時は 30 分）
+    #[serde(default)]
+    pub conversation_idle_minutes: Option<u64>,
+    /// キャラ設定のプリセットを保存するファイル（省略時は gemini_presets.json）
+    #[serde(default)]
+    pub presets_path: Option<String>,
+    /// AI コマンドの利用制限（省略時は制限なし）
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+/// LLM のバックエンドの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProviderKind {
+    /// Google の Gemini API
+    #[default]
+    Gemini,
+    /// OpenAI 互換の Chat Completions API（OpenAI, Ollama, llama.cpp など）
+    OpenaiCompatible,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RateLimitConfig {
+    /// 同じユーザーが続けて使うときに空ける秒数
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    /// ユーザーごとの 1 日あたりの上限回数
+    #[serde(default)]
+    pub user_daily_limit: Option<u32>,
+    /// サーバー全体の 1 日あたりの上限回数
+    #[serde(default)]
+    pub guild_daily_limit: Option<u32>,
+}
+
+impl RateLimitConfig {
+    pub fn settings(&self) -> RateLimitSettings {
+        RateLimitSettings {
+            cooldown: Duration::from_secs(self.cooldown_secs),
+            user_daily_limit: self.user_daily_limit,
+            guild_daily_limit: self.guild_daily_limit,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DicConfig {
-    pub spreadsheet_id: String,
-    pub service_account_key_path: String,
-    pub db_spreadsheet_url: String,
+    /// 辞書の保存先（省略時は google_sheets）
+    #[serde(default)]
+    pub backend: TriggerBackendKind,
+    /// スプレッドシートの ID（google_sheets では必須）
+    #[serde(default)]
+    pub spreadsheet_id: Option<String>,
+    /// サービスアカウントの鍵ファイルのパス（省略時は `GOOGLE_APPLICATION_CREDENTIALS` かメタデータサーバーを使う。
+    /// `GOOGLE_SERVICE_ACCOUNT_KEY_JSON` 環境変数があればそちらを優先する）
+    #[serde(default)]
+    pub service_account_key_path: Option<String>,
+    /// 埋め込みの「Check DB」のリンク先（省略時はリンクを表示しない）
+    #[serde(default)]
+    pub db_spreadsheet_url: Option<String>,
+    /// 辞書の JSON ファイルのパス（local では必須）
+    #[serde(default)]
+    pub local_path: Option<String>,
+    /// トリガー表のキャッシュを取り直すまでの秒数（省略時は 300 秒）
+    #[serde(default)]
+    pub cache_ttl_secs: Option<u64>,
+    /// `/dic add` と `/dic edit` でシートに書き込めるようにする（サービスアカウントに編集権限が必要）
+    #[serde(default)]
+    pub allow_edit: bool,
+    /// 追加・編集ができるロール（サーバー管理権限を持つメンバーは常に可能）
+    #[serde(default)]
+    pub editor_role_ids: Vec<u64>,
+    /// 発言にキーワードが含まれていたら自動で返信するチャンネル（省略時は自動返信しない）
+    #[serde(default)]
+    pub auto_response_channel_ids: Vec<u64>,
+    /// 同じチャンネルで同じ項目に自動返信するまでに空ける秒数（省略時は 300 秒）
+    #[serde(default)]
+    pub auto_response_cooldown_secs: Option<u64>,
+    /// 項目ごとの表示回数を保存するファイル（省略時は dic_hits.json）
+    #[serde(default)]
+    pub hits_path: Option<String>,
+}
+
+/// `/dic` の辞書の保存先の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerBackendKind {
+    /// Google スプレッドシートの `trigger` シート
+    #[default]
+    GoogleSheets,
+    /// ローカルの JSON ファイル
+    Local,
+}
+
+impl DicConfig {
+    /// 選んだ保存先に必要な項目が設定されているか確認する
+    fn validate(&self) -> Result<(), String> {
+        match self.backend {
+            TriggerBackendKind::GoogleSheets if self.spreadsheet_id.is_none() => {
+                Err("dic.spreadsheet_id is required for the google_sheets backend".to_string())
+            }
+            TriggerBackendKind::Local if self.local_path.is_none() => {
+                Err("dic.local_path is required for the local backend".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 設定内容から辞書の保存先を作成する
+    ///
+    /// 必要な項目が揃っているかは `Config::load` で確認済み。
+    pub fn trigger_store(&self) -> Arc<dyn TriggerStore> {
+        match self.backend {
+            TriggerBackendKind::GoogleSheets => {
+                let credentials =
+                    google_auth::default_provider(self.service_account_key_path.as_deref());
+                let spreadsheet_id = self.spreadsheet_id.as_deref().unwrap_or_default();
+                let mut client = SheetsClient::new(credentials, spreadsheet_id);
+                if self.allow_edit {
+                    client = client.with_scope(google_sheets::SCOPE_READ_WRITE);
+                }
+                let mut sheet = TriggerSheet::new(client);
+                if let Some(ttl_secs) = self.cache_ttl_secs {
+                    sheet = sheet.with_ttl(Duration::from_secs(ttl_secs));
+                }
+                Arc::new(sheet)
+            }
+            TriggerBackendKind::Local => {
+                let path = self.local_path.as_deref().unwrap_or_default();
+                Arc::new(LocalTriggerStore::new(path))
+            }
+        }
+    }
+}
+
+impl GeminiConfig {
+    /// 選んだバックエンドに必要な項目が設定されているか確認する
+    fn validate(&self) -> Result<(), String> {
+        match self.provider {
+            LlmProviderKind::Gemini if self.api_key.is_none() => {
+                Err("gemini.api_key is required for the gemini provider".to_string())
+            }
+            LlmProviderKind::OpenaiCompatible if self.model.is_none() => {
+                Err("gemini.model is required for the openai_compatible provider".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 設定内容から LLM のクライアントを作成する
+    ///
+    /// 必要な項目が揃っているかは `Config::load` で確認済み。
+    pub fn provider(&self) -> Arc<dyn LlmProvider> {
+        let timeout = self.timeout_secs.map(Duration::from_secs);
+        match self.provider {
+            LlmProviderKind::Gemini => {
+                let mut client = GeminiClient::new(self.api_key.as_deref().unwrap_or_default());
+                if let Some(model) = &self.model {
+                    client = client.with_model(model);
+                }
+                if let Some(base_url) = &self.base_url {
+                    client = client.with_base_url(base_url);
+                }
+                if let Some(timeout) = timeout {
+                    client = client.with_timeout(timeout);
+                }
+                Arc::new(client)
+            }
+            LlmProviderKind::OpenaiCompatible => {
+                let mut client = OpenAiClient::new(self.model.as_deref().unwrap_or_default());
+                if let Some(api_key) = &self.api_key {
+                    client = client.with_api_key(api_key);
+                }
+                if let Some(base_url) = &self.base_url {
+                    client = client.with_base_url(base_url);
+                }
+                if let Some(timeout) = timeout {
+                    client = client.with_timeout(timeout);
+                }
+                Arc::new(client)
+            }
+        }
+    }
+
+    /// 設定内容から AI コマンドの利用制限を作成する
+    pub fn rate_limiter(&self) -> RateLimiter {
+        let settings = self
+            .rate_limit
+            .as_ref()
+            .map(RateLimitConfig::settings)
+            .unwrap_or_default();
+        RateLimiter::new(settings)
+    }
+
+    /// 設定内容からスレッド会話の履歴ストアを作成する
+    pub fn conversation_store(&self) -> ConversationStore {
+        let token_budget = self
+            .conversation_token_budget
+            .unwrap_or(conversation::DEFAULT_TOKEN_BUDGET);
+        let idle_timeout = self
+            .conversation_idle_minutes
+            .map(|minutes| Duration::from_secs(minutes * 60))
+            .unwrap_or(conversation::DEFAULT_IDLE_TIMEOUT);
+        ConversationStore::new(token_budget, idle_timeout)
+    }
 }
 
 impl Config {
@@ -52,33 +322,67 @@ impl Config {
     /// なければ `config.json` ファイルから読み込む（ローカル開発用）。
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         if let Ok(json) = std::env::var("CONFIG_JSON") {
-            let config: Config = serde_json::from_str(&json)?;
-            config.write_service_account_key_if_present()?;
-            return Ok(config);
+            return Self::from_json(&json);
         }
         Self::load_from_path("config.json")
     }
 
-    /// `GOOGLE_SERVICE_ACCOUNT_KEY_JSON` 環境変数が設定されていれば、
-    /// `dic.service_account_key_path` が指すパスにその内容を書き出す。
-    /// Railway のようなコンテナ環境では鍵ファイルを直接配置できないための対応。
-    fn write_service_account_key_if_present(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let (Some(dic), Ok(key_json)) =
-            (&self.dic, std::env::var("GOOGLE_SERVICE_ACCOUNT_KEY_JSON"))
-        else {
-            return Ok(());
-        };
-        if let Some(parent) = Path::new(&dic.service_account_key_path).parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::write(&dic.service_account_key_path, key_json)?;
-        Ok(())
-    }
-
     /// 指定されたパスから設定ファイルを読み込む
     pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
         let contents = fs::read_to_string(path)?;
-        let config: Config = serde_json::from_str(&contents)?;
+        Self::from_json(&contents)
+    }
+
+    /// JSON の設定を読み込み、必要な項目が揃っているか確認する
+    fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: Config = serde_json::from_str(json)?;
+        if let Some(gemini) = &config.gemini {
+            gemini.validate()?;
+        }
+        if let Some(dic) = &config.dic {
+            dic.validate()?;
+        }
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gemini_provider_requires_api_key() {
+        let error = Config::from_json(r#"{"discord_token": "x", "guild_id": 1, "gemini": {}}"#)
+            .unwrap_err();
+        assert!(error.to_string().contains("gemini.api_key"));
+    }
+
+    #[test]
+    fn openai_compatible_provider_requires_model() {
+        let json =
+            r#"{"discord_token": "x", "guild_id": 1, "gemini": {"provider": "openai_compatible"}}"#;
+        let error = Config::from_json(json).unwrap_err();
+        assert!(error.to_string().contains("gemini.model"));
+    }
+
+    #[test]
+    fn openai_compatible_provider_does_not_need_api_key() {
+        let json = r#"{"discord_token": "x", "guild_id": 1, "gemini": {"provider": "openai_compatible", "model": "llama3"}}"#;
+        let config = Config::from_json(json).unwrap();
+        assert_eq!(config.gemini.unwrap().provider().model(), "llama3");
+    }
+
+    #[test]
+    fn google_sheets_backend_requires_spreadsheet_id() {
+        let error =
+            Config::from_json(r#"{"discord_token": "x", "guild_id": 1, "dic": {}}"#).unwrap_err();
+        assert!(error.to_string().contains("dic.spreadsheet_id"));
+    }
+
+    #[test]
+    fn local_backend_requires_local_path() {
+        let json = r#"{"discord_token": "x", "guild_id": 1, "dic": {"backend": "local"}}"#;
+        let error = Config::from_json(json).unwrap_err();
+        assert!(error.to_string().contains("dic.local_path"));
+    }
+}
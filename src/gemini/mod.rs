@@ -0,0 +1,419 @@
+pub mod presets;
+pub mod provider;
+pub mod stream;
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{api_error_message, http_client};
+pub use presets::{CharacterPreset, PresetStore};
+pub use stream::GeminiStream;
+
+pub const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+pub const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Gemini API のクライアント
+#[derive(Debug, Clone)]
+pub struct GeminiClient {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    /// `generateContent` 全体のタイムアウト。ストリーミングではデータが届く間隔のタイムアウトとして使う
+    timeout: Duration,
+}
+
+impl GeminiClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            http: http_client(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            model: DEFAULT_MODEL.to_string(),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// API のベース URL を差し替える（テスト用のモックサーバーなど）
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = http_client(timeout);
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// `generateContent` を呼び出す
+    pub async fn generate_content(
+        &self,
+        request: &GenerateContentRequest,
+    ) -> Result<GenerateContentResponse, GeminiError> {
+        let url = format!("{}/models/{}:generateContent", self.base_url, self.model);
+
+        let response = self
+            .http
+            .post(&url)
+            .query(&[("key", &self.api_key)])
+            .json(request)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(GeminiError::from_reqwest)?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(GeminiError::from_reqwest)?;
+
+        if !status.is_success() {
+            return Err(GeminiError::Api {
+                status: status.as_u16(),
+                message: api_error_message(&body),
+            });
+        }
+
+        serde_json::from_str(&body).map_err(|source| GeminiError::Parse { source, body })
+    }
+
+    /// `streamGenerateContent` を SSE で呼び出し、生成された順に応答を受け取るストリームを返す
+    ///
+    /// 長い回答でも途中で打ち切らないよう、全体のタイムアウトは設けない。
+    /// 接続と、次のデータが届くまでの間隔にだけタイムアウトがある。
+    pub async fn stream_generate_content(
+        &self,
+        request: &GenerateContentRequest,
+    ) -> Result<GeminiStream, GeminiError> {
+        let url = format!(
+            "{}/models/{}:streamGenerateContent",
+            self.base_url, self.model
+        );
+
+        let response = self
+            .http
+            .post(&url)
+            .query(&[("alt", "sse"), ("key", &self.api_key)])
+            .json(request)
+            .send()
+            .await
+            .map_err(GeminiError::from_reqwest)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.map_err(GeminiError::from_reqwest)?;
+            return Err(GeminiError::Api {
+                status: status.as_u16(),
+                message: api_error_message(&body),
+            });
+        }
+
+        Ok(GeminiStream::new(response))
+    }
+
+    /// `generateContent` を呼び出し、最初の候補のテキストと使用量を返す
+    pub async fn generate_text(
+        &self,
+        request: &GenerateContentRequest,
+    ) -> Result<GeminiAnswer, GeminiError> {
+        let response = self.generate_content(request).await?;
+        let text = response.text()?;
+        Ok(GeminiAnswer {
+            text,
+            usage: response.usage_metadata,
+        })
+    }
+}
+
+/// テキスト生成の結果
+#[derive(Debug, Clone)]
+pub struct GeminiAnswer {
+    pub text: String,
+    pub usage: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentRequest {
+    pub contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<GenerationConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub safety_settings: Vec<SafetySetting>,
+}
+
+impl GenerateContentRequest {
+    pub fn new(contents: Vec<Content>) -> Self {
+        Self {
+            contents,
+            ..Default::default()
+        }
+    }
+
+    /// ユーザーの発言 1 つだけのリクエストを作る
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self::new(vec![Content::user(text)])
+    }
+
+    pub fn with_system_instruction(mut self, text: impl Into<String>) -> Self {
+        self.system_instruction = Some(Content::text(None, text));
+        self
+    }
+
+    pub fn with_generation_config(mut self, config: GenerationConfig) -> Self {
+        self.generation_config = Some(config);
+        self
+    }
+
+    pub fn with_safety_setting(
+        mut self,
+        category: impl Into<String>,
+        threshold: impl Into<String>,
+    ) -> Self {
+        self.safety_settings.push(SafetySetting {
+            category: category.into(),
+            threshold: threshold.into(),
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Content {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+impl Content {
+    fn text(role: Option<&str>, text: impl Into<String>) -> Self {
+        Self {
+            role: role.map(|r| r.to_string()),
+            parts: vec![Part::text(text)],
+        }
+    }
+
+    pub fn user(text: impl Into<String>) -> Self {
+        Self::text(Some("user"), text)
+    }
+
+    /// 複数のパートからなるユーザーの発言を作る
+    pub fn user_parts(parts: Vec<Part>) -> Self {
+        Self {
+            role: Some("user".to_string()),
+            parts,
+        }
+    }
+
+    pub fn model(text: impl Into<String>) -> Self {
+        Self::text(Some("model"), text)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Part {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_data: Option<InlineData>,
+}
+
+impl Part {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// 画像などのバイナリを base64 にして埋め込む
+    pub fn inline_data(mime_type: impl Into<String>, data: &[u8]) -> Self {
+        Self {
+            inline_data: Some(InlineData {
+                mime_type: mime_type.into(),
+                data: BASE64_STANDARD.encode(data),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineData {
+    pub mime_type: String,
+    /// base64 でエンコードしたデータ
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+}
+
+/// 安全性フィルタの設定（例: `HARM_CATEGORY_HARASSMENT` / `BLOCK_ONLY_HIGH`）
+#[derive(Debug, Clone, Serialize)]
+pub struct SafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentResponse {
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+    pub usage_metadata: Option<UsageMetadata>,
+    pub prompt_feedback: Option<PromptFeedback>,
+    pub model_version: Option<String>,
+}
+
+impl GenerateContentResponse {
+    /// 最初の候補のテキストをそのまま連結して返す（ストリーミングの途中の断片など、空でもよい場合に使う）
+    pub fn partial_text(&self) -> String {
+        self.candidates
+            .first()
+            .iter()
+            .flat_map(|c| c.content.iter())
+            .flat_map(|c| c.parts.iter())
+            .filter_map(|p| p.text.as_deref())
+            .collect()
+    }
+
+    /// 最初の候補のテキストを連結して返す
+    pub fn text(&self) -> Result<String, GeminiError> {
+        if let Some(reason) = self
+            .prompt_feedback
+            .as_ref()
+            .and_then(|f| f.block_reason.as_ref())
+        {
+            return Err(GeminiError::Blocked(reason.clone()));
+        }
+
+        let Some(candidate) = self.candidates.first() else {
+            return Err(GeminiError::EmptyResponse);
+        };
+
+        let text = self.partial_text();
+
+        if text.is_empty() {
+            return Err(match candidate.finish_reason.as_deref() {
+                Some(reason @ ("SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT")) => {
+                    GeminiError::Blocked(reason.to_string())
+                }
+                _ => GeminiError::EmptyResponse,
+            });
+        }
+
+        Ok(text)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub content: Option<Content>,
+    pub finish_reason: Option<String>,
+}
+
+/// トークン使用量（`usageMetadata`）
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    pub prompt_token_count: u32,
+    #[serde(default)]
+    pub candidates_token_count: u32,
+    #[serde(default)]
+    pub thoughts_token_count: u32,
+    #[serde(default)]
+    pub total_token_count: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFeedback {
+    pub block_reason: Option<String>,
+}
+
+/// Gemini API 呼び出しのエラー
+#[derive(Debug)]
+pub enum GeminiError {
+    /// リクエストがタイムアウトした
+    Timeout,
+    /// 接続失敗などの HTTP レベルのエラー
+    Request(reqwest::Error),
+    /// API がエラーステータスを返した
+    Api { status: u16, message: String },
+    /// レスポンスの JSON を解析できなかった
+    Parse {
+        source: serde_json::Error,
+        body: String,
+    },
+    /// 安全性フィルタなどで応答がブロックされた
+    Blocked(String),
+    /// 候補やテキストが含まれていなかった
+    EmptyResponse,
+}
+
+impl GeminiError {
+    pub(crate) fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            GeminiError::Timeout
+        } else {
+            GeminiError::Request(e)
+        }
+    }
+}
+
+impl fmt::Display for GeminiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeminiError::Timeout => write!(f, "Gemini API request timed out"),
+            GeminiError::Request(e) => write!(f, "Gemini API request failed: {}", e),
+            GeminiError::Api { status, message } => {
+                write!(f, "Gemini API error: HTTP {} - {}", status, message)
+            }
+            GeminiError::Parse { source, body } => {
+                write!(f, "Failed to parse Gemini response ({}): {}", source, body)
+            }
+            GeminiError::Blocked(reason) => write!(f, "Gemini response was blocked: {}", reason),
+            GeminiError::EmptyResponse => write!(f, "Gemini response has no candidates/parts"),
+        }
+    }
+}
+
+impl Error for GeminiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GeminiError::Request(e) => Some(e),
+            GeminiError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
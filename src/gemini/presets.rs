@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_store::JsonFileStore;
+
+/// 名前を付けて保存したキャラ設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterPreset {
+    pub name: String,
+    pub prompt: String,
+    /// 作成したユーザーの ID
+    pub created_by: u64,
+}
+
+/// サーバーごとのキャラ設定を JSON ファイルに保存する
+pub struct PresetStore {
+    file: JsonFileStore<HashMap<u64, Vec<CharacterPreset>>>,
+}
+
+impl PresetStore {
+    /// 空の状態で作成する（保存するまでファイルには触れない）
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: JsonFileStore::new(path),
+        }
+    }
+
+    /// ファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            file: JsonFileStore::load(path)?,
+        })
+    }
+
+    pub fn get(&self, guild_id: u64, name: &str) -> Option<CharacterPreset> {
+        self.file.read(|presets| {
+            presets
+                .get(&guild_id)?
+                .iter()
+                .find(|p| p.name == name)
+                .cloned()
+        })
+    }
+
+    /// 名前順に並べたキャラ設定の一覧を返す
+    pub fn list(&self, guild_id: u64) -> Vec<CharacterPreset> {
+        let mut list = self
+            .file
+            .read(|presets| presets.get(&guild_id).cloned().unwrap_or_default());
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    /// 名前に `query` を含むキャラ設定を返す（前方一致を優先）
+    pub fn search(&self, guild_id: u64, query: &str, limit: usize) -> Vec<CharacterPreset> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<CharacterPreset> = self
+            .list(guild_id)
+            .into_iter()
+            .filter(|p| p.name.to_lowercase().contains(&query))
+            .collect();
+        matches.sort_by_key(|p| !p.name.to_lowercase().starts_with(&query));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// キャラ設定を追加する。同じ名前がすでにあれば追加せずに `false` を返す
+    pub async fn insert(&self, guild_id: u64, preset: CharacterPreset) -> io::Result<bool> {
+        if self.get(guild_id, &preset.name).is_some() {
+            return Ok(false);
+        }
+        self.file
+            .update(|presets| {
+                let guild_presets = presets.entry(guild_id).or_default();
+                if guild_presets.iter().any(|p| p.name == preset.name) {
+                    return false;
+                }
+                guild_presets.push(preset);
+                true
+            })
+            .await
+    }
+
+    /// キャラ設定を削除し、削除したものを返す
+    pub async fn remove(&self, guild_id: u64, name: &str) -> io::Result<Option<CharacterPreset>> {
+        if self.get(guild_id, name).is_none() {
+            return Ok(None);
+        }
+        self.file
+            .update(|presets| {
+                let guild_presets = presets.get_mut(&guild_id)?;
+                let index = guild_presets.iter().position(|p| p.name == name)?;
+                Some(guild_presets.remove(index))
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(name: &str) -> CharacterPreset {
+        CharacterPreset {
+            name: name.to_string(),
+            prompt: "あなたはタコです。".to_string(),
+            created_by: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_insert_is_not_kept_in_memory() {
+        // /dev/null の下にはファイルを作れないので保存に失敗する
+        let store = PresetStore::new("/dev/null/presets.json");
+
+        assert!(store.insert(1, preset("tako")).await.is_err());
+        assert!(store.get(1, "tako").is_none());
+    }
+}
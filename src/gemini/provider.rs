@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+
+use super::{
+    Content, GeminiClient, GeminiError, GeminiStream, GenerateContentRequest, GenerationConfig,
+    Part, UsageMetadata,
+};
+use crate::llm::{
+    LlmAnswer, LlmChunk, LlmError, LlmProvider, LlmRequest, LlmStream, Message, MessagePart, Role,
+    Usage,
+};
+
+#[async_trait]
+impl LlmProvider for GeminiClient {
+    fn service_name(&self) -> &'static str {
+        "Gemini API"
+    }
+
+    fn model(&self) -> &str {
+        GeminiClient::model(self)
+    }
+
+    async fn generate(&self, request: &LlmRequest) -> Result<LlmAnswer, LlmError> {
+        let answer = self.generate_text(&request.into()).await?;
+        Ok(LlmAnswer {
+            text: answer.text,
+            usage: answer.usage.map(Usage::from),
+        })
+    }
+
+    async fn stream(&self, request: &LlmRequest) -> Result<Box<dyn LlmStream>, LlmError> {
+        let stream = self.stream_generate_content(&request.into()).await?;
+        Ok(Box::new(stream))
+    }
+}
+
+#[async_trait]
+impl LlmStream for GeminiStream {
+    async fn next_chunk(&mut self) -> Option<Result<LlmChunk, LlmError>> {
+        let response = match self.next().await? {
+            Ok(response) => response,
+            Err(e) => return Some(Err(e.into())),
+        };
+        Some(Ok(LlmChunk {
+            text: response.partial_text(),
+            usage: response.usage_metadata.map(Usage::from),
+        }))
+    }
+}
+
+impl From<&LlmRequest> for GenerateContentRequest {
+    fn from(request: &LlmRequest) -> Self {
+        let mut converted =
+            GenerateContentRequest::new(request.messages.iter().map(Content::from).collect());
+        if let Some(system) = &request.system {
+            converted = converted.with_system_instruction(system);
+        }
+        if request.temperature.is_some() || request.max_output_tokens.is_some() {
+            converted = converted.with_generation_config(GenerationConfig {
+                temperature: request.temperature,
+                max_output_tokens: request.max_output_tokens,
+                ..Default::default()
+            });
+        }
+        converted
+    }
+}
+
+impl From<&Message> for Content {
+    fn from(message: &Message) -> Self {
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "model",
+        };
+        Content {
+            role: Some(role.to_string()),
+            parts: message
+                .parts
+                .iter()
+                .map(|part| match part {
+                    MessagePart::Text(text) => Part::text(text),
+                    MessagePart::Image { mime_type, data } => Part::inline_data(mime_type, data),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<UsageMetadata> for Usage {
+    fn from(usage: UsageMetadata) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.total_token_count,
+        }
+    }
+}
+
+impl From<GeminiError> for LlmError {
+    fn from(e: GeminiError) -> Self {
+        match e {
+            GeminiError::Timeout => LlmError::Timeout,
+            GeminiError::Request(e) => LlmError::Request(e),
+            GeminiError::Api { status, message } => LlmError::Api { status, message },
+            GeminiError::Parse { source, body } => {
+                LlmError::Parse(format!("{} (body: {})", source, body))
+            }
+            GeminiError::Blocked(reason) => LlmError::Blocked(reason),
+            GeminiError::EmptyResponse => LlmError::EmptyResponse,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use super::{GeminiError, GenerateContentResponse};
+use crate::sse::SseReader;
+
+/// `streamGenerateContent` の SSE を 1 イベントずつ読み出す
+pub struct GeminiStream {
+    reader: SseReader,
+}
+
+impl GeminiStream {
+    pub(crate) fn new(response: reqwest::Response) -> Self {
+        Self {
+            reader: SseReader::new(response),
+        }
+    }
+
+    /// 次の応答の断片を返す。ストリームが終わったら `None` を返す
+    pub async fn next(&mut self) -> Option<Result<GenerateContentResponse, GeminiError>> {
+        let data = match self.reader.next_event().await? {
+            Ok(data) => data,
+            Err(e) => return Some(Err(GeminiError::from_reqwest(e))),
+        };
+        Some(
+            serde_json::from_str(&data).map_err(|source| GeminiError::Parse { source, body: data }),
+        )
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// サービスアカウントの鍵を中身ごと渡す環境変数（ファイルには書き出さない）
+pub const KEY_JSON_ENV: &str = "GOOGLE_SERVICE_ACCOUNT_KEY_JSON";
+/// Application Default Credentials の認証情報ファイルを指す環境変数
+pub const APPLICATION_CREDENTIALS_ENV: &str = "GOOGLE_APPLICATION_CREDENTIALS";
+/// GCE / Cloud Run のメタデータサーバーのトークン取得 URL
+pub const DEFAULT_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+/// `authorized_user` の認証情報ファイルに `token_uri` がないときに使う URL
+const DEFAULT_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// 取得したアクセストークン
+#[derive(Debug, Deserialize)]
+pub struct AccessToken {
+    pub access_token: String,
+    /// 有効期間（秒）
+    #[serde(default = "default_expires_in")]
+    pub expires_in: u64,
+}
+
+fn default_expires_in() -> u64 {
+    3600
+}
+
+/// Google API のアクセストークンを取得する方法
+///
+/// キャッシュは呼び出し側（`SheetsClient` など）が行う。
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    async fn fetch_token(
+        &self,
+        http: &reqwest::Client,
+        scope: &str,
+    ) -> Result<AccessToken, BoxError>;
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizedUser {
+    client_id: String,
+    client_secret: String,
+    refresh_token: String,
+    #[serde(default)]
+    token_uri: Option<String>,
+}
+
+/// `GOOGLE_APPLICATION_CREDENTIALS` などの認証情報ファイルの中身（`type` で種類を区別する）
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CredentialsFile {
+    ServiceAccount(ServiceAccountKey),
+    /// `gcloud auth application-default login` で作られるユーザーの認証情報
+    AuthorizedUser(AuthorizedUser),
+}
+
+#[derive(Debug, Serialize)]
+struct Claims {
+    iss: String,
+    scope: String,
+    aud: String,
+    iat: i64,
+    exp: i64,
+}
+
+enum CredentialsSource {
+    Json(String),
+    File(PathBuf),
+}
+
+/// サービスアカウントの鍵や `authorized_user` の認証情報ファイルでトークンを取得する
+///
+/// 鍵はトークンを取得するたびに読み直すので、起動後にファイルを置き換えても反映される。
+pub struct CredentialsProvider {
+    source: CredentialsSource,
+    /// 認証情報の `token_uri` の代わりに使う OAuth のトークン URL（ローカルのモックサーバーで試す場合など）
+    token_url: Option<String>,
+}
+
+impl CredentialsProvider {
+    /// 認証情報の JSON をそのまま使う（環境変数から渡された鍵など。ディスクには書き出さない）
+    pub fn from_json(json: impl Into<String>) -> Self {
+        Self {
+            source: CredentialsSource::Json(json.into()),
+            token_url: None,
+        }
+    }
+
+    /// 認証情報のファイルを読んで使う
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: CredentialsSource::File(path.into()),
+            token_url: None,
+        }
+    }
+
+    pub fn with_token_url(mut self, token_url: impl Into<String>) -> Self {
+        self.token_url = Some(token_url.into());
+        self
+    }
+
+    async fn load(&self) -> Result<CredentialsFile, BoxError> {
+        let json = match &self.source {
+            CredentialsSource::Json(json) => json.clone(),
+            CredentialsSource::File(path) => tokio::fs::read_to_string(path).await?,
+        };
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+#[async_trait]
+impl TokenProvider for CredentialsProvider {
+    async fn fetch_token(
+        &self,
+        http: &reqwest::Client,
+        scope: &str,
+    ) -> Result<AccessToken, BoxError> {
+        let request = match self.load().await? {
+            CredentialsFile::ServiceAccount(key) => {
+                let now = chrono::Utc::now().timestamp();
+                let claims = Claims {
+                    iss: key.client_email,
+                    scope: scope.to_string(),
+                    aud: key.token_uri.clone(),
+                    iat: now,
+                    exp: now + 3600,
+                };
+
+                let header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256);
+                let encoding_key =
+                    jsonwebtoken::EncodingKey::from_rsa_pem(key.private_key.as_bytes())?;
+                let jwt = jsonwebtoken::encode(&header, &claims, &encoding_key)?;
+
+                http.post(self.token_url.as_deref().unwrap_or(&key.token_uri))
+                    .form(&[
+                        ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                        ("assertion", &jwt),
+                    ])
+            }
+            CredentialsFile::AuthorizedUser(user) => {
+                let token_url = self
+                    .token_url
+                    .clone()
+                    .or(user.token_uri)
+                    .unwrap_or_else(|| DEFAULT_TOKEN_URL.to_string());
+                http.post(token_url).form(&[
+                    ("grant_type", "refresh_token"),
+                    ("client_id", &user.client_id),
+                    ("client_secret", &user.client_secret),
+                    ("refresh_token", &user.refresh_token),
+                ])
+            }
+        };
+
+        Ok(request.send().await?.error_for_status()?.json().await?)
+    }
+}
+
+/// GCE や Cloud Run のメタデータサーバーからサービスアカウントのトークンを取得する
+///
+/// 鍵を持たずに、実行環境に割り当てられたサービスアカウント（Workload Identity）で認証する。
+pub struct MetadataServerProvider {
+    token_url: String,
+}
+
+impl MetadataServerProvider {
+    pub fn new() -> Self {
+        Self {
+            token_url: DEFAULT_METADATA_TOKEN_URL.to_string(),
+        }
+    }
+
+    pub fn with_token_url(mut self, token_url: impl Into<String>) -> Self {
+        self.token_url = token_url.into();
+        self
+    }
+}
+
+impl Default for MetadataServerProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TokenProvider for MetadataServerProvider {
+    async fn fetch_token(
+        &self,
+        http: &reqwest::Client,
+        scope: &str,
+    ) -> Result<AccessToken, BoxError> {
+        Ok(http
+            .get(&self.token_url)
+            .query(&[("scopes", scope)])
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+/// 実行環境に合わせて認証方法を選ぶ
+///
+/// 次の順に、最初に見つかったものを使う。
+/// 1. `GOOGLE_SERVICE_ACCOUNT_KEY_JSON` 環境変数の鍵（ディスクには書き出さない）
+/// 2. `key_path`（設定で指定された鍵ファイル）
+/// 3. `GOOGLE_APPLICATION_CREDENTIALS` 環境変数が指す認証情報ファイル
+/// 4. GCE / Cloud Run のメタデータサーバー
+pub fn default_provider(key_path: Option<&str>) -> Arc<dyn TokenProvider> {
+    if let Ok(json) = std::env::var(KEY_JSON_ENV) {
+        return Arc::new(CredentialsProvider::from_json(json));
+    }
+    if let Some(path) = key_path {
+        return Arc::new(CredentialsProvider::from_file(path));
+    }
+    if let Ok(path) = std::env::var(APPLICATION_CREDENTIALS_ENV) {
+        return Arc::new(CredentialsProvider::from_file(path));
+    }
+    Arc::new(MetadataServerProvider::new())
+}
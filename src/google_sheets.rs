@@ -1,141 +1,361 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::google_auth::TokenProvider;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 読み取り専用のスコープ
+pub const SCOPE_READONLY: &str = "https://www.googleapis.com/auth/spreadsheets.readonly";
+/// 読み書きできるスコープ（`/dic add` などでシートに書き込む場合）
+pub const SCOPE_READ_WRITE: &str = "https://www.googleapis.com/auth/spreadsheets";
+/// Sheets API の既定のベース URL
+pub const DEFAULT_SHEETS_BASE_URL: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+/// アクセストークンの期限が切れる少し前に取り直すための余裕
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+/// 接続できるまで待つ時間
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// リクエスト 1 回あたりの制限時間（Google が応答しないときに `/dic` が待ち続けないように）
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
 
 #[derive(Debug, Deserialize)]
-struct ServiceAccountKey {
-    client_email: String,
-    private_key: String,
-    token_uri: String,
+struct ValueRange {
+    values: Option<Vec<Vec<String>>>,
 }
 
-#[derive(Debug, serde::Serialize)]
-struct Claims {
-    iss: String,
-    scope: String,
-    aud: String,
-    iat: i64,
-    exp: i64,
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
 }
 
-#[derive(Debug, Deserialize)]
-struct TokenResponse {
-    access_token: String,
+/// ヘッダー行の列名で値を引けるようにした 1 行分のデータ
+#[derive(Debug, Clone)]
+pub struct SheetRow {
+    /// シート上の行番号（1 始まり）
+    pub number: usize,
+    /// ヘッダーの列名とセルの値（空のセルは空文字列）
+    pub fields: HashMap<String, String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct SheetsResponse {
-    values: Option<Vec<Vec<String>>>,
+impl SheetRow {
+    /// 列名に対応するセルの値を返す（列がなければ空文字列）
+    pub fn get(&self, column: &str) -> &str {
+        self.fields
+            .get(column)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// 列名をフィールド名として serde で構造体に読み替える
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        let object = self
+            .fields
+            .iter()
+            .map(|(name, value)| (name.clone(), serde_json::Value::String(value.clone())))
+            .collect();
+        serde_json::from_value(serde_json::Value::Object(object))
+    }
+}
+
+/// ヘッダー行とその下のデータ行
+#[derive(Debug, Clone, Default)]
+pub struct SheetTable {
+    /// ヘッダー行の列名（左から順に）
+    pub header: Vec<String>,
+    pub rows: Vec<SheetRow>,
 }
 
-#[derive(Debug)]
-pub struct TriggerEntry {
-    pub response: String,
-    pub title: String,
-    pub description: String,
-    pub thumbnail_url: String,
-    pub image_url: String,
+impl SheetTable {
+    /// シートの値を読み取る（`header_row` は 1 始まりの行番号。その下の行をデータとする）
+    fn parse(values: Vec<Vec<String>>, header_row: usize) -> Self {
+        let mut values = values.into_iter();
+        let Some(header) = values.nth(header_row.saturating_sub(1)) else {
+            return Self::default();
+        };
+        let header: Vec<String> = header.iter().map(|h| h.trim().to_string()).collect();
+
+        let rows = values
+            .enumerate()
+            .map(|(i, cells)| SheetRow {
+                number: header_row + i + 1,
+                fields: header
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| !name.is_empty())
+                    .map(|(col, name)| (name.clone(), cells.get(col).cloned().unwrap_or_default()))
+                    .collect(),
+            })
+            .collect();
+
+        Self { header, rows }
+    }
+
+    /// 各行を serde で構造体に読み替える
+    pub fn deserialize_rows<T: DeserializeOwned>(&self) -> Result<Vec<T>, serde_json::Error> {
+        self.rows.iter().map(SheetRow::deserialize).collect()
+    }
 }
 
-async fn get_access_token(
-    service_account_key_path: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let key_data = tokio::fs::read_to_string(service_account_key_path).await?;
-    let key: ServiceAccountKey = serde_json::from_str(&key_data)?;
-
-    let now = chrono::Utc::now().timestamp();
-    let claims = Claims {
-        iss: key.client_email,
-        scope: "https://www.googleapis.com/auth/spreadsheets.readonly".to_string(),
-        aud: key.token_uri.clone(),
-        iat: now,
-        exp: now + 3600,
-    };
-
-    let header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256);
-    let encoding_key = jsonwebtoken::EncodingKey::from_rsa_pem(key.private_key.as_bytes())?;
-    let jwt = jsonwebtoken::encode(&header, &claims, &encoding_key)?;
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&key.token_uri)
-        .form(&[
-            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
-            ("assertion", &jwt),
-        ])
-        .send()
-        .await?;
-
-    let response = response.error_for_status()?;
-    let token_response: TokenResponse = response.json().await?;
-    Ok(token_response.access_token)
+/// Google Sheets API を呼び出すクライアント
+///
+/// 認証は `TokenProvider` に任せ、取得したアクセストークンは期限が切れる少し前までメモリ上にキャッシュする。
+/// `clone()` したものはアクセストークンのキャッシュを共有する。
+#[derive(Clone)]
+pub struct SheetsClient {
+    http: reqwest::Client,
+    credentials: Arc<dyn TokenProvider>,
+    spreadsheet_id: String,
+    scope: String,
+    base_url: String,
+    token: Arc<tokio::sync::Mutex<Option<CachedToken>>>,
 }
 
-pub async fn search_trigger(
-    service_account_key_path: &str,
-    spreadsheet_id: &str,
-    keyword: &str,
-) -> Result<Option<TriggerEntry>, Box<dyn std::error::Error + Send + Sync>> {
-    let access_token = get_access_token(service_account_key_path).await?;
-
-    let url = format!(
-        "https://sheets.googleapis.com/v4/spreadsheets/{}/values/trigger",
-        spreadsheet_id
-    );
-
-    let client = reqwest::Client::new();
-    let response = client.get(&url).bearer_auth(&access_token).send().await?;
-
-    let response = response.error_for_status()?;
-    let sheets_response: SheetsResponse = response.json().await?;
-
-    let values = match sheets_response.values {
-        Some(v) => v,
-        None => return Ok(None),
-    };
-
-    // values[1] is the header row (spreadsheet row 2)
-    if values.len() < 3 {
-        return Ok(None);
-    }
-    let header = &values[1];
-
-    // Find column indices for search fields
-    let trigger_col = header.iter().position(|h| h == "trigger");
-    let alias01_col = header.iter().position(|h| h == "alias01");
-    let alias02_col = header.iter().position(|h| h == "alias02");
-
-    // Find column indices for result fields
-    let response_col = header.iter().position(|h| h == "response");
-    let title_col = header.iter().position(|h| h == "title");
-    let description_col = header.iter().position(|h| h == "description");
-    let thumbnail_col = header.iter().position(|h| h == "right_small_image_URL");
-    let image_col = header.iter().position(|h| h == "big_image_URL");
-
-    let search_cols = [trigger_col, alias01_col, alias02_col];
-    let keyword_lower = keyword.to_lowercase();
-
-    // Search data rows (starting from index 2)
-    for row in values.iter().skip(2) {
-        let matched = search_cols.iter().any(|col| {
-            col.and_then(|idx| row.get(idx))
-                .is_some_and(|cell| cell.to_lowercase() == keyword_lower)
+impl SheetsClient {
+    pub fn new(credentials: Arc<dyn TokenProvider>, spreadsheet_id: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            credentials,
+            spreadsheet_id: spreadsheet_id.into(),
+            scope: SCOPE_READONLY.to_string(),
+            base_url: DEFAULT_SHEETS_BASE_URL.to_string(),
+            token: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    /// アクセストークンのスコープを設定する（書き込む場合は `SCOPE_READ_WRITE`）
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = scope.into();
+        self
+    }
+
+    /// Sheets API のベース URL を設定する（ローカルのモックサーバーで試す場合など）
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// 範囲の値をそのまま取得する（`values.get`）
+    pub async fn get_values(&self, range: &str) -> Result<Vec<Vec<String>>, BoxError> {
+        let access_token = self.access_token().await?;
+        let value_range: ValueRange = self
+            .http
+            .get(self.values_url(range))
+            .bearer_auth(&access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(value_range.values.unwrap_or_default())
+    }
+
+    /// 1 行分の値を取得する（行番号は 1 始まり）
+    pub async fn get_row(&self, sheet: &str, row_number: usize) -> Result<Vec<String>, BoxError> {
+        let range = format!("{}!{}:{}", sheet, row_number, row_number);
+        Ok(self
+            .get_values(&range)
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default())
+    }
+
+    /// シート全体を読み、`header_row` 行目をヘッダーとして列名で引ける行にする
+    pub async fn get_table(&self, sheet: &str, header_row: usize) -> Result<SheetTable, BoxError> {
+        Ok(SheetTable::parse(self.get_values(sheet).await?, header_row))
+    }
+
+    /// シート全体を読み、ヘッダーより下の各行を serde で構造体に読み替える
+    pub async fn get_rows<T: DeserializeOwned>(
+        &self,
+        sheet: &str,
+        header_row: usize,
+    ) -> Result<Vec<T>, BoxError> {
+        Ok(self
+            .get_table(sheet, header_row)
+            .await?
+            .deserialize_rows()?)
+    }
+
+    /// 表の末尾に行を追加する（`values.append`）
+    pub async fn append(&self, range: &str, rows: &[Vec<String>]) -> Result<(), BoxError> {
+        let access_token = self.access_token().await?;
+        self.http
+            .post(format!("{}:append", self.values_url(range)))
+            .query(&[
+                ("valueInputOption", "RAW"),
+                ("insertDataOption", "INSERT_ROWS"),
+            ])
+            .bearer_auth(&access_token)
+            .json(&serde_json::json!({ "values": rows }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// 範囲に値を書き込む（`values.update`）
+    pub async fn update(&self, range: &str, rows: &[Vec<String>]) -> Result<(), BoxError> {
+        let access_token = self.access_token().await?;
+        self.http
+            .put(self.values_url(range))
+            .query(&[("valueInputOption", "RAW")])
+            .bearer_auth(&access_token)
+            .json(&serde_json::json!({ "values": rows }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// 複数の範囲にまとめて値を書き込む（`values.batchUpdate`）
+    pub async fn batch_update(
+        &self,
+        updates: &[(String, Vec<Vec<String>>)],
+    ) -> Result<(), BoxError> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+        let data: Vec<serde_json::Value> = updates
+            .iter()
+            .map(|(range, rows)| serde_json::json!({ "range": range, "values": rows }))
+            .collect();
+        let access_token = self.access_token().await?;
+        self.http
+            .post(format!(
+                "{}/{}/values:batchUpdate",
+                self.base_url, self.spreadsheet_id
+            ))
+            .bearer_auth(&access_token)
+            .json(&serde_json::json!({ "valueInputOption": "RAW", "data": data }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// 範囲の値を消す（`values.clear`）
+    pub async fn clear(&self, range: &str) -> Result<(), BoxError> {
+        let access_token = self.access_token().await?;
+        self.http
+            .post(format!("{}:clear", self.values_url(range)))
+            .bearer_auth(&access_token)
+            .json(&serde_json::json!({}))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn values_url(&self, range: &str) -> String {
+        format!(
+            "{}/{}/values/{}",
+            self.base_url,
+            self.spreadsheet_id,
+            urlencoding::encode(range)
+        )
+    }
+
+    /// キャッシュしたアクセストークンを返す。期限が近ければ取り直す
+    async fn access_token(&self) -> Result<String, BoxError> {
+        let mut token = self.token.lock().await;
+        if let Some(cached) = token.as_ref()
+            && Instant::now() + TOKEN_EXPIRY_MARGIN < cached.expires_at
+        {
+            return Ok(cached.access_token.clone());
+        }
+
+        let response = self
+            .credentials
+            .fetch_token(&self.http, &self.scope)
+            .await?;
+        let access_token = response.access_token.clone();
+        *token = Some(CachedToken {
+            access_token: response.access_token,
+            expires_at: Instant::now() + Duration::from_secs(response.expires_in),
         });
+        Ok(access_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
 
-        if matched {
-            let get_field = |col: Option<usize>| -> String {
-                col.and_then(|idx| row.get(idx))
-                    .map(|s| s.to_string())
-                    .unwrap_or_default()
-            };
-
-            return Ok(Some(TriggerEntry {
-                response: get_field(response_col),
-                title: get_field(title_col),
-                description: get_field(description_col),
-                thumbnail_url: get_field(thumbnail_col),
-                image_url: get_field(image_col),
-            }));
+    use super::*;
+    use crate::google_auth::AccessToken;
+
+    /// 呼ばれた回数を数え、毎回別のトークンを返す
+    struct StubProvider {
+        expires_in: u64,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TokenProvider for StubProvider {
+        async fn fetch_token(
+            &self,
+            _http: &reqwest::Client,
+            _scope: &str,
+        ) -> Result<AccessToken, BoxError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(AccessToken {
+                access_token: format!("token-{n}"),
+                expires_in: self.expires_in,
+            })
         }
     }
 
-    Ok(None)
+    fn client(expires_in: u64) -> (SheetsClient, Arc<StubProvider>) {
+        let provider = Arc::new(StubProvider {
+            expires_in,
+            calls: AtomicUsize::new(0),
+        });
+        (SheetsClient::new(provider.clone(), "sheet"), provider)
+    }
+
+    #[tokio::test]
+    async fn access_token_is_cached_until_near_expiry() {
+        let (client, provider) = client(3600);
+
+        assert_eq!(client.access_token().await.unwrap(), "token-1");
+        // clone() したものもキャッシュを共有する
+        assert_eq!(client.clone().access_token().await.unwrap(), "token-1");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn access_token_close_to_expiry_is_fetched_again() {
+        // 有効期間が余裕より短いので、毎回取り直す
+        let (client, provider) = client(TOKEN_EXPIRY_MARGIN.as_secs() / 2);
+
+        assert_eq!(client.access_token().await.unwrap(), "token-1");
+        assert_eq!(client.access_token().await.unwrap(), "token-2");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn table_header_is_trimmed() {
+        let values = vec![
+            vec!["title".to_string()],
+            vec![" trigger ".to_string(), "tags".to_string()],
+            vec!["genkai".to_string()],
+        ];
+        let table = SheetTable::parse(values, 2);
+
+        assert_eq!(table.header, ["trigger", "tags"]);
+        assert_eq!(table.rows[0].number, 3);
+        assert_eq!(table.rows[0].get("trigger"), "genkai");
+        assert_eq!(table.rows[0].get("tags"), "");
+    }
 }
@@ -0,0 +1,134 @@
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 値をメモリ上に持ち、変更するたびに JSON ファイルに書き出す
+///
+/// キャラ設定のプリセットや `/dic` の表示回数など、Bot が自分で書き換える小さなデータの保存に使う。
+/// 書き出しは一時ファイルに書いてから置き換えるので、書き込み途中のファイルは残らない。
+pub struct JsonFileStore<T> {
+    path: PathBuf,
+    value: Mutex<T>,
+    /// 書き出しの順番が入れ替わって古い内容で上書きしないよう、変更を 1 つずつ行うためのロック
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl<T> JsonFileStore<T>
+where
+    T: Serialize + DeserializeOwned + Clone + Default + Send + 'static,
+{
+    /// 空の状態で作成する（変更するまでファイルには触れない）
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self::with_value(path.as_ref().to_path_buf(), T::default())
+    }
+
+    /// ファイルから読み込む。ファイルがなければ空の状態で始める
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = path.as_ref().to_path_buf();
+        let value = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => T::default(),
+            Err(e) => return Err(Box::new(e)),
+        };
+        Ok(Self::with_value(path, value))
+    }
+
+    fn with_value(path: PathBuf, value: T) -> Self {
+        Self {
+            path,
+            value: Mutex::new(value),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 現在の値を読む
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.value.lock().unwrap())
+    }
+
+    /// 値を変更してファイルに書き出す
+    ///
+    /// 変更は複製に対して行い、書き出せた場合だけメモリ上の値に反映する。
+    /// 書き出しに失敗した場合は値を変えずにエラーを返す。
+    pub async fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> io::Result<R> {
+        let _guard = self.write_lock.lock().await;
+
+        let mut next = self.read(T::clone);
+        let result = f(&mut next);
+
+        let json = serde_json::to_string_pretty(&next)?;
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || write_atomically(&path, &json))
+            .await
+            .map_err(io::Error::other)??;
+
+        *self.value.lock().unwrap() = next;
+        Ok(result)
+    }
+}
+
+/// 一時ファイルに書いてから置き換える（同期 I/O なので async の中では `spawn_blocking` から呼ぶ）
+pub(crate) fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("json_store_{}_{}.json", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn update_is_written_and_loaded_again() {
+        let path = temp_path("roundtrip");
+        let store: JsonFileStore<HashMap<String, u32>> = JsonFileStore::new(&path);
+        store
+            .update(|counts| counts.insert("genkai".to_string(), 3))
+            .await
+            .unwrap();
+
+        let loaded: JsonFileStore<HashMap<String, u32>> = JsonFileStore::load(&path).unwrap();
+        assert_eq!(loaded.read(|counts| counts.get("genkai").copied()), Some(3));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_update_keeps_the_old_value() {
+        // /dev/null の下にはファイルを作れないので書き出しに失敗する
+        let store: JsonFileStore<Vec<u32>> = JsonFileStore::new("/dev/null/store.json");
+
+        assert!(store.update(|values| values.push(1)).await.is_err());
+        assert!(store.read(Vec::is_empty));
+    }
+
+    #[test]
+    fn missing_file_starts_empty() {
+        let store: JsonFileStore<Vec<u32>> = JsonFileStore::load(temp_path("missing")).unwrap();
+        assert!(store.read(Vec::is_empty));
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let path = temp_path("corrupt");
+        fs::write(&path, "{not json").unwrap();
+
+        assert!(JsonFileStore::<Vec<u32>>::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
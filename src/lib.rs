@@ -1,2 +1,12 @@
+pub mod gemini;
+pub mod google_auth;
 pub mod google_sheets;
+pub mod json_store;
+pub mod llm;
+pub mod normalize;
+pub mod openai;
+pub mod rate_limit;
+pub mod sse;
+pub mod trigger;
 pub mod utils;
+pub mod weather;
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{LlmRequest, Message, MessagePart};
+
+pub const DEFAULT_TOKEN_BUDGET: usize = 8000;
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// 1 つのスレッドでの会話
+#[derive(Debug, Clone)]
+struct Conversation {
+    system_instruction: String,
+    history: Vec<Message>,
+    last_active: Instant,
+}
+
+impl Conversation {
+    fn estimated_tokens(&self) -> usize {
+        self.history.iter().map(estimate_tokens).sum()
+    }
+
+    /// 履歴がトークン予算を超えていれば、古いやり取りから削除する
+    ///
+    /// ユーザーとモデルの発言は必ず対で削除し、履歴がユーザーの発言から始まるようにする。
+    /// 直近のやり取りは予算を超えていても残す。
+    fn trim(&mut self, token_budget: usize) {
+        while self.history.len() > 2 && self.estimated_tokens() > token_budget {
+            self.history.drain(..2);
+        }
+    }
+}
+
+/// 画像 1 枚あたりのトークン数の目安
+const IMAGE_TOKENS: usize = 258;
+
+/// トークン数の概算（日本語はおおよそ 1 文字 1 トークンなので文字数で数える）
+fn estimate_tokens(message: &Message) -> usize {
+    message
+        .parts
+        .iter()
+        .map(|p| match p {
+            MessagePart::Text(text) => text.chars().count(),
+            MessagePart::Image { .. } => IMAGE_TOKENS,
+        })
+        .sum()
+}
+
+/// スレッドごとの会話履歴を保持する
+///
+/// 一定時間発言のない会話は期限切れとして破棄する。
+pub struct ConversationStore {
+    conversations: Mutex<HashMap<u64, Conversation>>,
+    token_budget: usize,
+    idle_timeout: Duration,
+}
+
+impl Default for ConversationStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_TOKEN_BUDGET, DEFAULT_IDLE_TIMEOUT)
+    }
+}
+
+impl ConversationStore {
+    pub fn new(token_budget: usize, idle_timeout: Duration) -> Self {
+        Self {
+            conversations: Mutex::new(HashMap::new()),
+            token_budget,
+            idle_timeout,
+        }
+    }
+
+    /// 新しい会話を開始する（最初の質問と回答を履歴に入れる）
+    pub fn start(
+        &self,
+        id: u64,
+        system_instruction: impl Into<String>,
+        question: Message,
+        answer: impl Into<String>,
+    ) {
+        let mut conversation = Conversation {
+            system_instruction: system_instruction.into(),
+            history: vec![question, Message::assistant(answer)],
+            last_active: Instant::now(),
+        };
+        conversation.trim(self.token_budget);
+
+        let mut conversations = self.conversations.lock().unwrap();
+        self.remove_expired(&mut conversations);
+        conversations.insert(id, conversation);
+    }
+
+    /// 会話の履歴に新しい発言を加えたリクエストを作る
+    ///
+    /// 会話が存在しないか期限切れの場合は `None` を返す。
+    pub fn request_for(&self, id: u64, message: &str) -> Option<LlmRequest> {
+        let mut conversations = self.conversations.lock().unwrap();
+        self.remove_expired(&mut conversations);
+        let conversation = conversations.get(&id)?;
+
+        let mut messages = conversation.history.clone();
+        messages.push(Message::user(message));
+        Some(LlmRequest::new(messages).with_system(&conversation.system_instruction))
+    }
+
+    /// 発言と回答を履歴に追加する
+    pub fn record(&self, id: u64, message: impl Into<String>, answer: impl Into<String>) {
+        let mut conversations = self.conversations.lock().unwrap();
+        let Some(conversation) = conversations.get_mut(&id) else {
+            return;
+        };
+
+        conversation.history.push(Message::user(message));
+        conversation.history.push(Message::assistant(answer));
+        conversation.last_active = Instant::now();
+        conversation.trim(self.token_budget);
+    }
+
+    fn remove_expired(&self, conversations: &mut HashMap<u64, Conversation>) {
+        conversations.retain(|_, c| c.last_active.elapsed() < self.idle_timeout);
+    }
+}
@@ -0,0 +1,237 @@
+pub mod conversation;
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+pub use conversation::ConversationStore;
+
+/// 接続を確立するまでのタイムアウト
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// LLM のバックエンド（Gemini, OpenAI 互換 API など）
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// エラーメッセージなどに表示するサービス名
+    fn service_name(&self) -> &'static str;
+
+    /// 使用しているモデル名
+    fn model(&self) -> &str;
+
+    /// 回答を最後まで生成してから返す
+    async fn generate(&self, request: &LlmRequest) -> Result<LlmAnswer, LlmError>;
+
+    /// 生成された順に回答の断片を受け取るストリームを返す
+    async fn stream(&self, request: &LlmRequest) -> Result<Box<dyn LlmStream>, LlmError>;
+}
+
+/// 回答の断片を順に受け取るストリーム
+#[async_trait]
+pub trait LlmStream: Send {
+    /// 次の断片を返す。ストリームが終わったら `None` を返す
+    async fn next_chunk(&mut self) -> Option<Result<LlmChunk, LlmError>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone)]
+pub enum MessagePart {
+    Text(String),
+    /// 画像などのバイナリ
+    Image {
+        mime_type: String,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub role: Role,
+    pub parts: Vec<MessagePart>,
+}
+
+impl Message {
+    pub fn user(text: impl Into<String>) -> Self {
+        Self::user_parts(vec![MessagePart::Text(text.into())])
+    }
+
+    pub fn user_parts(parts: Vec<MessagePart>) -> Self {
+        Self {
+            role: Role::User,
+            parts,
+        }
+    }
+
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            parts: vec![MessagePart::Text(text.into())],
+        }
+    }
+
+    /// テキストのパートだけを連結して返す
+    pub fn text(&self) -> String {
+        self.parts
+            .iter()
+            .filter_map(|p| match p {
+                MessagePart::Text(text) => Some(text.as_str()),
+                MessagePart::Image { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LlmRequest {
+    pub system: Option<String>,
+    pub messages: Vec<Message>,
+    pub temperature: Option<f32>,
+    pub max_output_tokens: Option<u32>,
+}
+
+impl LlmRequest {
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self {
+            messages,
+            ..Default::default()
+        }
+    }
+
+    /// ユーザーの発言 1 つだけのリクエストを作る
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self::new(vec![Message::user(text)])
+    }
+
+    pub fn with_system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+}
+
+/// トークン使用量
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+/// 生成された回答
+#[derive(Debug, Clone)]
+pub struct LlmAnswer {
+    pub text: String,
+    pub usage: Option<Usage>,
+}
+
+/// ストリーミングで受け取る回答の断片
+#[derive(Debug, Clone, Default)]
+pub struct LlmChunk {
+    pub text: String,
+    /// 使用量（最後の断片にだけ含まれることが多い）
+    pub usage: Option<Usage>,
+}
+
+/// LLM 呼び出しのエラー
+#[derive(Debug)]
+pub enum LlmError {
+    /// リクエストがタイムアウトした
+    Timeout,
+    /// 接続失敗などの HTTP レベルのエラー
+    Request(reqwest::Error),
+    /// API がエラーステータスを返した
+    Api { status: u16, message: String },
+    /// レスポンスを解析できなかった
+    Parse(String),
+    /// 安全性フィルタなどで応答がブロックされた
+    Blocked(String),
+    /// 回答のテキストが含まれていなかった
+    EmptyResponse,
+}
+
+impl LlmError {
+    pub fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            LlmError::Timeout
+        } else {
+            LlmError::Request(e)
+        }
+    }
+}
+
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LlmError::Timeout => write!(f, "LLM request timed out"),
+            LlmError::Request(e) => write!(f, "LLM request failed: {}", e),
+            LlmError::Api { status, message } => {
+                write!(f, "LLM API error: HTTP {} - {}", status, message)
+            }
+            LlmError::Parse(message) => write!(f, "Failed to parse LLM response: {}", message),
+            LlmError::Blocked(reason) => write!(f, "LLM response was blocked: {}", reason),
+            LlmError::EmptyResponse => write!(f, "LLM response has no text"),
+        }
+    }
+}
+
+impl Error for LlmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LlmError::Request(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// 接続と読み取りのそれぞれにタイムアウトを設定した HTTP クライアントを作る
+///
+/// `read_timeout` はデータが届く間隔に対するもので、ストリーミングの応答全体の長さは制限しない。
+pub(crate) fn http_client(read_timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(read_timeout)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorResponse {
+    error: ApiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    message: String,
+}
+
+/// API のエラーレスポンス（`{"error": {"message": ...}}`）からメッセージを取り出す
+///
+/// Gemini API と OpenAI 互換 API のどちらも同じ形で返す。JSON でなければ本文をそのまま返す。
+pub(crate) fn api_error_message(body: &str) -> String {
+    serde_json::from_str::<ApiErrorResponse>(body)
+        .map(|e| e.error.message)
+        .unwrap_or_else(|_| body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_error_message_reads_json_error() {
+        let body = r#"{"error": {"code": 400, "message": "API key not valid", "status": "INVALID_ARGUMENT"}}"#;
+        assert_eq!(api_error_message(body), "API key not valid");
+    }
+
+    #[test]
+    fn api_error_message_falls_back_to_body() {
+        assert_eq!(api_error_message("Bad Gateway"), "Bad Gateway");
+    }
+}
@@ -2,108 +2,94 @@ mod commands;
 mod config;
 mod scheduled_tasks;
 
+use commands::CommandRegistry;
 use config::Config;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use serenity::async_trait;
 use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMessage};
 use serenity::model::application::Interaction;
+use serenity::model::channel::Message;
 use serenity::model::gateway::Ready;
 use serenity::model::id::GuildId;
 use serenity::prelude::*;
 
-#[derive(Default)]
 struct Handler {
+    config: Config,
+    commands: CommandRegistry,
     scheduled_tasks_started: AtomicBool,
 }
 
+impl Handler {
+    fn new(config: Config) -> Self {
+        let commands = CommandRegistry::new(&config);
+        Self {
+            config,
+            commands,
+            scheduled_tasks_started: AtomicBool::new(false),
+        }
+    }
+}
+
 #[async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            // println!("Received command interaction: {command:#?}");
-
-            let content = match command.data.name.as_str() {
-                "ping" => Some(commands::ping::run(&command.data.options())),
-                "modal" => {
-                    commands::modal::run(&ctx, &command).await.unwrap();
-                    None
-                }
-                "mt" => {
-                    commands::marimo::run(&ctx, &command).await.unwrap();
-                    None
-                }
-                "count" => {
-                    commands::count::run(&ctx, &command).await.unwrap();
-                    None
-                }
-                "debug_weather" => {
-                    commands::debug_weather::run(&ctx, &command).await.unwrap();
-                    None
-                }
-                "debug_stock" => {
-                    commands::debug_stock::run(&ctx, &command).await.unwrap();
-                    None
-                }
-                "dic" => {
-                    commands::dic::run(&ctx, &command).await.unwrap();
-                    None
-                }
-                "gemini" => {
-                    commands::gemini::run(&ctx, &command).await.unwrap();
-                    None
+        match interaction {
+            Interaction::Command(command) => {
+                // println!("Received command interaction: {command:#?}");
+
+                let Some(slash_command) = self.commands.find(&command.data.name, command.data.kind)
+                else {
+                    let data =
+                        CreateInteractionResponseMessage::new().content("not implemented :(");
+                    let builder = CreateInteractionResponse::Message(data);
+                    if let Err(why) = command.create_response(&ctx.http, builder).await {
+                        println!("Cannot respond to slash command: {why}");
+                    }
+                    return;
+                };
+
+                if let Err(why) = slash_command.run(&ctx, &command).await {
+                    commands::error::report_error(&ctx, &command, &why).await;
                 }
-                _ => Some("not implemented :(".to_string()),
-            };
-
-            if let Some(content) = content {
-                let data = CreateInteractionResponseMessage::new().content(content);
-                let builder = CreateInteractionResponse::Message(data);
-                if let Err(why) = command.create_response(&ctx.http, builder).await {
-                    println!("Cannot respond to slash command: {why}");
+            }
+            Interaction::Autocomplete(command) => {
+                let Some(slash_command) = self.commands.find(&command.data.name, command.data.kind)
+                else {
+                    return;
+                };
+
+                if let Err(why) = slash_command.autocomplete(&ctx, &command).await {
+                    eprintln!("[/{}] Autocomplete failed: {why}", command.data.name);
                 }
             }
+            _ => {}
         }
     }
 
-    async fn ready(&self, ctx: Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
-
-        // 設定ファイルを読み込む
-        let config = Config::load().expect("Failed to load config.json");
-        let guild_id = GuildId::new(config.guild_id);
-
-        // Guild コマンドをセットする
-        let mut command_list = vec![
-            commands::ping::register(),
-            commands::modal::register(),
-            commands::count::register(),
-            commands::marimo::register(),
-        ];
-
-        // dic コマンドを条件付きで追加
-        if config.dic.is_some() {
-            command_list.push(commands::dic::register());
+    async fn message(&self, ctx: Context, new_message: Message) {
+        if new_message.author.bot {
+            return;
         }
+        self.commands.dispatch_message(&ctx, &new_message).await;
+    }
 
-        // gemini コマンドを条件付きで追加
-        if config.gemini.is_some() {
-            command_list.push(commands::gemini::register());
-        }
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        println!("{} is connected!", ready.user.name);
 
-        // デバッグコマンドを条件付きで追加
-        if config.debug_slash_commands {
-            command_list.push(commands::debug_weather::register());
-            command_list.push(commands::debug_stock::register());
-        }
+        let guild_id = GuildId::new(self.config.guild_id);
 
-        let commands = guild_id.set_commands(&ctx.http, command_list).await;
+        // Guild コマンドをセットする（スラッシュコマンドと右クリックメニューのコマンドをまとめて登録する。
+        // 有効なコマンドは CommandRegistry が設定に基づいて決める）
+        let commands = guild_id
+            .set_commands(&ctx.http, self.commands.create_commands())
+            .await;
 
         println!("I now have the following guild slash commands: {commands:#?}");
 
-        // Global コマンドを作成する場合は以下のコメントアウトを外して Command をインポートする
+        // Global コマンドを作成する場合は以下のコメントアウトを外して Command と SlashCommand をインポートする
         // let guild_command =
-        //     Command::create_global_command(&ctx.http, commands::wonderful_command::register())
+        //     Command::create_global_command(&ctx.http, commands::wonderful_command::Wonderful.register())
         //         .await;
 
         // println!("I created the following global slash command: {guild_command:#?}");
@@ -114,7 +100,7 @@ impl EventHandler for Handler {
             .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
             .is_ok()
         {
-            let tasks = scheduled_tasks::create_scheduled_tasks(&config.scheduled_tasks);
+            let tasks = scheduled_tasks::create_scheduled_tasks(&self.config);
             scheduled_tasks::start_scheduled_tasks(ctx.clone(), tasks).await;
             println!("Scheduled tasks have been started.");
         } else {
@@ -130,10 +116,12 @@ async fn main() {
         "Failed to load config.json. Please create config.json based on config.example.json",
     );
     let token = config.discord_token.clone();
+    let handler = Handler::new(config);
+    let intents = handler.commands.intents();
 
     // Build our client.
-    let mut client = Client::builder(token, GatewayIntents::empty())
-        .event_handler(Handler::default())
+    let mut client = Client::builder(token, intents)
+        .event_handler(handler)
         .await
         .expect("Error creating client");
 
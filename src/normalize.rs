@@ -0,0 +1,106 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// キーワードの表記ゆれを吸収するために正規化する
+///
+/// - NFKC で正規化する（全角英数字・記号と全角スペースは半角に、半角カタカナは全角に、
+///   ①・㍻ のような互換文字は元の文字に、濁点・半濁点は直前のかなと合成する）
+/// - カタカナをひらがなにする
+/// - 小文字にし、前後の空白を取り除いて連続する空白を 1 つにまとめる
+pub fn normalize_keyword(text: &str) -> String {
+    text.chars()
+        .map(spacing_to_combining_mark)
+        .nfkc()
+        .map(katakana_to_hiragana)
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 単独の濁点・半濁点（゛゜）を結合文字にする
+///
+/// NFKC は単独の濁点を「空白 + 結合文字」に分解して直前のかなと合成しないため、先に置き換えておく。
+fn spacing_to_combining_mark(c: char) -> char {
+    match c {
+        '\u{309B}' => '\u{3099}',
+        '\u{309C}' => '\u{309A}',
+        _ => c,
+    }
+}
+
+fn katakana_to_hiragana(c: char) -> char {
+    match c {
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// 2 つの文字列の編集距離（文字単位のレーベンシュタイン距離）
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_width_and_case() {
+        assert_eq!(normalize_keyword("ＧｅｎＫａｉ"), "genkai");
+        assert_eq!(normalize_keyword("ｹﾞﾝｶｲ"), "げんかい");
+        assert_eq!(normalize_keyword("ﾊﾟﾝ"), "ぱん");
+    }
+
+    #[test]
+    fn folds_katakana_to_hiragana() {
+        assert_eq!(
+            normalize_keyword("トクイビョウ"),
+            normalize_keyword("とくいびょう")
+        );
+        assert_eq!(normalize_keyword("ヴ"), "ゔ");
+    }
+
+    #[test]
+    fn combines_voiced_sound_marks() {
+        // 結合文字の濁点・半濁点
+        assert_eq!(normalize_keyword("か\u{3099}"), "が");
+        assert_eq!(normalize_keyword("ハ\u{309A}"), "ぱ");
+        // 単独の濁点・半濁点
+        assert_eq!(normalize_keyword("か゛"), "が");
+        assert_eq!(normalize_keyword("ホ゜"), "ぽ");
+    }
+
+    #[test]
+    fn folds_compatibility_characters() {
+        assert_eq!(normalize_keyword("①"), "1");
+        assert_eq!(normalize_keyword("㍻"), "平成");
+        assert_eq!(normalize_keyword("＄１００"), "$100");
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        assert_eq!(normalize_keyword("\u{3000} gomi \t  bako "), "gomi bako");
+    }
+
+    #[test]
+    fn edit_distance_counts_characters() {
+        assert_eq!(edit_distance("genkai", "genkai"), 0);
+        assert_eq!(edit_distance("genkai", "genkia"), 2);
+        assert_eq!(edit_distance("げんかい", "げんか"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
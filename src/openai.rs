@@ -0,0 +1,321 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{
+    LlmAnswer, LlmChunk, LlmError, LlmProvider, LlmRequest, LlmStream, MessagePart, Role, Usage,
+    api_error_message, http_client,
+};
+use crate::sse::SseReader;
+
+/// Ollama をローカルで動かしたときのエンドポイント
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434/v1";
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// OpenAI 互換の Chat Completions API のクライアント
+///
+/// OpenAI のほか、Ollama や llama.cpp の `server` など互換 API を持つサーバーに使える。
+#[derive(Debug, Clone)]
+pub struct OpenAiClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    /// 回答を最後まで生成するときの全体のタイムアウト。ストリーミングではデータが届く間隔のタイムアウトとして使う
+    timeout: Duration,
+}
+
+impl OpenAiClient {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            http: http_client(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: None,
+            model: model.into(),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// API キーを設定する（ローカルのサーバーでは不要なことが多い）
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = http_client(timeout);
+        self.timeout = timeout;
+        self
+    }
+
+    /// `/chat/completions` にリクエストを送り、成功したレスポンスを返す
+    ///
+    /// ストリーミングでは長い回答を途中で打ち切らないよう、全体のタイムアウトを設けない。
+    async fn send(&self, body: &ChatRequest<'_>) -> Result<reqwest::Response, LlmError> {
+        let mut builder = self
+            .http
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(body);
+        if !body.stream {
+            builder = builder.timeout(self.timeout);
+        }
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+
+        let response = builder.send().await.map_err(LlmError::from_reqwest)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.map_err(LlmError::from_reqwest)?;
+            return Err(LlmError::Api {
+                status: status.as_u16(),
+                message: api_error_message(&body),
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiClient {
+    fn service_name(&self) -> &'static str {
+        "OpenAI 互換 API"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn generate(&self, request: &LlmRequest) -> Result<LlmAnswer, LlmError> {
+        let body = ChatRequest::new(&self.model, request, false);
+        let response = self.send(&body).await?;
+        let body = response.text().await.map_err(LlmError::from_reqwest)?;
+        let response: ChatResponse = serde_json::from_str(&body)
+            .map_err(|e| LlmError::Parse(format!("{} (body: {})", e, body)))?;
+
+        let Some(choice) = response.choices.into_iter().next() else {
+            return Err(LlmError::EmptyResponse);
+        };
+        if choice.finish_reason.as_deref() == Some("content_filter") {
+            return Err(LlmError::Blocked("content_filter".to_string()));
+        }
+        let text = choice.message.content.unwrap_or_default();
+        if text.is_empty() {
+            return Err(LlmError::EmptyResponse);
+        }
+
+        Ok(LlmAnswer {
+            text,
+            usage: response.usage.map(Usage::from),
+        })
+    }
+
+    async fn stream(&self, request: &LlmRequest) -> Result<Box<dyn LlmStream>, LlmError> {
+        let body = ChatRequest::new(&self.model, request, true);
+        let response = self.send(&body).await?;
+        Ok(Box::new(OpenAiStream {
+            reader: SseReader::new(response),
+        }))
+    }
+}
+
+/// `stream: true` の SSE を 1 イベントずつ読み出す
+struct OpenAiStream {
+    reader: SseReader,
+}
+
+#[async_trait]
+impl LlmStream for OpenAiStream {
+    async fn next_chunk(&mut self) -> Option<Result<LlmChunk, LlmError>> {
+        let data = match self.reader.next_event().await? {
+            Ok(data) => data,
+            Err(e) => return Some(Err(LlmError::from_reqwest(e))),
+        };
+        // 最後に `[DONE]` が送られてくる
+        if data == "[DONE]" {
+            return None;
+        }
+
+        let chunk: ChatChunk = match serde_json::from_str(&data) {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(LlmError::Parse(format!("{} (body: {})", e, data)))),
+        };
+        if let Some(choice) = chunk.choices.first()
+            && choice.finish_reason.as_deref() == Some("content_filter")
+        {
+            return Some(Err(LlmError::Blocked("content_filter".to_string())));
+        }
+
+        Some(Ok(LlmChunk {
+            text: chunk
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|c| c.delta.content)
+                .unwrap_or_default(),
+            usage: chunk.usage.map(Usage::from),
+        }))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+impl<'a> ChatRequest<'a> {
+    fn new(model: &'a str, request: &LlmRequest, stream: bool) -> Self {
+        let system = request.system.iter().map(|system| ChatMessage {
+            role: "system",
+            content: ChatContent::Text(system.clone()),
+        });
+        let messages = request.messages.iter().map(|message| ChatMessage {
+            role: match message.role {
+                Role::User => "user",
+                Role::Assistant => "assistant",
+            },
+            content: ChatContent::from_parts(&message.parts),
+        });
+
+        Self {
+            model,
+            messages: system.chain(messages).collect(),
+            temperature: request.temperature,
+            max_tokens: request.max_output_tokens,
+            stream,
+            // ストリーミングでも最後にトークン使用量を受け取る
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    role: &'static str,
+    content: ChatContent,
+}
+
+/// テキストだけなら文字列、画像を含む場合はパートの配列で送る
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ChatContent {
+    Text(String),
+    Parts(Vec<ChatPart>),
+}
+
+impl ChatContent {
+    fn from_parts(parts: &[MessagePart]) -> Self {
+        if let [MessagePart::Text(text)] = parts {
+            return ChatContent::Text(text.clone());
+        }
+        ChatContent::Parts(
+            parts
+                .iter()
+                .map(|part| match part {
+                    MessagePart::Text(text) => ChatPart::Text { text: text.clone() },
+                    MessagePart::Image { mime_type, data } => ChatPart::ImageUrl {
+                        image_url: ImageUrl {
+                            url: format!(
+                                "data:{};base64,{}",
+                                mime_type,
+                                BASE64_STANDARD.encode(data)
+                            ),
+                        },
+                    },
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ChatPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Debug, Serialize)]
+struct ImageUrl {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    choices: Vec<ChatChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ChunkDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChunkDelta {
+    content: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+    #[serde(default)]
+    total_tokens: u32,
+}
+
+impl From<ChatUsage> for Usage {
+    fn from(usage: ChatUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
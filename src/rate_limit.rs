@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Asia::Tokyo;
+
+/// レート制限の設定
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitSettings {
+    /// 同じユーザーが続けて使うときに空ける時間
+    pub cooldown: Duration,
+    /// ユーザーごとの 1 日あたりの上限回数
+    pub user_daily_limit: Option<u32>,
+    /// サーバー全体の 1 日あたりの上限回数
+    pub guild_daily_limit: Option<u32>,
+}
+
+/// 制限を超えたときのエラー（再試行できるまでの時間を持つ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitError {
+    Cooldown(Duration),
+    UserDailyLimit(Duration),
+    GuildDailyLimit(Duration),
+}
+
+impl RateLimitError {
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            RateLimitError::Cooldown(d)
+            | RateLimitError::UserDailyLimit(d)
+            | RateLimitError::GuildDailyLimit(d) => *d,
+        }
+    }
+}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::Cooldown(d) => write!(f, "cooldown ({}s left)", d.as_secs()),
+            RateLimitError::UserDailyLimit(d) => {
+                write!(f, "user daily limit reached (resets in {}s)", d.as_secs())
+            }
+            RateLimitError::GuildDailyLimit(d) => {
+                write!(f, "guild daily limit reached (resets in {}s)", d.as_secs())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
+/// その日の利用状況
+#[derive(Debug, Clone)]
+pub struct UsageSummary {
+    pub date: NaiveDate,
+    pub total: u32,
+    /// ユーザー ID と利用回数（回数の多い順）
+    pub users: Vec<(u64, u32)>,
+}
+
+#[derive(Debug)]
+struct State {
+    date: NaiveDate,
+    user_counts: HashMap<u64, u32>,
+    last_used: HashMap<u64, Instant>,
+}
+
+/// ユーザーごとのクールダウンと 1 日あたりの回数制限を管理する
+///
+/// 日付は日本時間で切り替わる。回数はメモリ上で数えているので、再起動するとリセットされる。
+pub struct RateLimiter {
+    settings: RateLimitSettings,
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub fn new(settings: RateLimitSettings) -> Self {
+        Self {
+            settings,
+            state: Mutex::new(State {
+                date: today_jst(),
+                user_counts: HashMap::new(),
+                last_used: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn settings(&self) -> &RateLimitSettings {
+        &self.settings
+    }
+
+    /// 利用できるか確認し、できれば 1 回分を予約する
+    ///
+    /// 確認と記録を同じロックの中で行うので、同時に届いたリクエストがまとめて制限をすり抜けることはない。
+    /// 返した `Reservation` を `commit` せずに破棄すると（外部 API の呼び出しに失敗したときなど）、予約は取り消される。
+    pub fn check(&self, user_id: u64) -> Result<Reservation<'_>, RateLimitError> {
+        let mut state = self.state.lock().unwrap();
+        roll_over(&mut state);
+
+        if let Some(last_used) = state.last_used.get(&user_id) {
+            let elapsed = last_used.elapsed();
+            if elapsed < self.settings.cooldown {
+                return Err(RateLimitError::Cooldown(self.settings.cooldown - elapsed));
+            }
+        }
+
+        if let Some(limit) = self.settings.guild_daily_limit {
+            let total: u32 = state.user_counts.values().sum();
+            if total >= limit {
+                return Err(RateLimitError::GuildDailyLimit(until_reset()));
+            }
+        }
+
+        let count = state.user_counts.get(&user_id).copied().unwrap_or(0);
+        if let Some(limit) = self.settings.user_daily_limit
+            && count >= limit
+        {
+            return Err(RateLimitError::UserDailyLimit(until_reset()));
+        }
+
+        let used_at = Instant::now();
+        *state.user_counts.entry(user_id).or_default() += 1;
+        let previous_used_at = state.last_used.insert(user_id, used_at);
+
+        Ok(Reservation {
+            limiter: self,
+            user_id,
+            date: state.date,
+            used_at,
+            previous_used_at,
+            committed: false,
+        })
+    }
+
+    /// 予約を取り消す（日付が変わっていれば、回数はすでにリセットされているので何もしない）
+    fn release(&self, reservation: &Reservation<'_>) {
+        let mut state = self.state.lock().unwrap();
+        roll_over(&mut state);
+        if state.date != reservation.date {
+            return;
+        }
+
+        if let Some(count) = state.user_counts.get_mut(&reservation.user_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.user_counts.remove(&reservation.user_id);
+            }
+        }
+        // 後から別の予約が時刻を書き換えていれば、そちらを残す
+        if state.last_used.get(&reservation.user_id) == Some(&reservation.used_at) {
+            match reservation.previous_used_at {
+                Some(previous) => state.last_used.insert(reservation.user_id, previous),
+                None => state.last_used.remove(&reservation.user_id),
+            };
+        }
+    }
+
+    /// 今日の利用状況を返す
+    pub fn usage(&self) -> UsageSummary {
+        let mut state = self.state.lock().unwrap();
+        roll_over(&mut state);
+
+        let mut users: Vec<(u64, u32)> = state
+            .user_counts
+            .iter()
+            .map(|(&id, &count)| (id, count))
+            .collect();
+        users.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        UsageSummary {
+            date: state.date,
+            total: users.iter().map(|(_, count)| count).sum(),
+            users,
+        }
+    }
+}
+
+/// `RateLimiter::check` で予約した 1 回分の利用
+#[must_use = "commit しないと予約は取り消される"]
+pub struct Reservation<'a> {
+    limiter: &'a RateLimiter,
+    user_id: u64,
+    date: NaiveDate,
+    used_at: Instant,
+    previous_used_at: Option<Instant>,
+    committed: bool,
+}
+
+impl Reservation<'_> {
+    /// 利用を確定する
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.limiter.release(self);
+        }
+    }
+}
+
+fn today_jst() -> NaiveDate {
+    Utc::now().with_timezone(&Tokyo).date_naive()
+}
+
+/// 日付が変わっていれば回数をリセットする
+fn roll_over(state: &mut State) {
+    let today = today_jst();
+    if state.date != today {
+        state.date = today;
+        state.user_counts.clear();
+    }
+}
+
+/// 日本時間の次の 0 時までの時間
+fn until_reset() -> Duration {
+    let now = Utc::now().with_timezone(&Tokyo);
+    let tomorrow = now.date_naive() + chrono::Duration::days(1);
+    let midnight = tomorrow
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Tokyo).single());
+    match midnight {
+        Some(midnight) => midnight
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or_default(),
+        None => Duration::from_secs(24 * 60 * 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(
+        cooldown_secs: u64,
+        user_limit: Option<u32>,
+        guild_limit: Option<u32>,
+    ) -> RateLimiter {
+        RateLimiter::new(RateLimitSettings {
+            cooldown: Duration::from_secs(cooldown_secs),
+            user_daily_limit: user_limit,
+            guild_daily_limit: guild_limit,
+        })
+    }
+
+    #[test]
+    fn check_reserves_quota_until_released() {
+        let limiter = limiter(60, Some(1), None);
+
+        let reservation = limiter.check(1).unwrap();
+        // 確定する前でも、続けて届いたリクエストは通さない
+        assert!(matches!(limiter.check(1), Err(RateLimitError::Cooldown(_))));
+        assert_eq!(limiter.usage().total, 1);
+
+        drop(reservation);
+        assert_eq!(limiter.usage().total, 0);
+        assert!(limiter.check(1).is_ok());
+    }
+
+    #[test]
+    fn cooldown_applies_after_commit() {
+        let limiter = limiter(60, None, None);
+        limiter.check(1).unwrap().commit();
+
+        assert!(matches!(limiter.check(1), Err(RateLimitError::Cooldown(_))));
+        assert!(limiter.check(2).is_ok());
+    }
+
+    #[test]
+    fn user_daily_limit() {
+        let limiter = limiter(0, Some(2), None);
+        limiter.check(1).unwrap().commit();
+        limiter.check(1).unwrap().commit();
+
+        assert!(matches!(
+            limiter.check(1),
+            Err(RateLimitError::UserDailyLimit(_))
+        ));
+        assert!(limiter.check(2).is_ok());
+    }
+
+    #[test]
+    fn concurrent_reservations_count_towards_the_limit() {
+        let limiter = limiter(0, Some(2), None);
+        let first = limiter.check(1).unwrap();
+        let _second = limiter.check(1).unwrap();
+
+        assert!(matches!(
+            limiter.check(1),
+            Err(RateLimitError::UserDailyLimit(_))
+        ));
+        // 片方が失敗して取り消されれば、また使える
+        drop(first);
+        assert!(limiter.check(1).is_ok());
+    }
+
+    #[test]
+    fn guild_daily_limit_counts_all_users() {
+        let limiter = limiter(0, None, Some(2));
+        limiter.check(1).unwrap().commit();
+        limiter.check(2).unwrap().commit();
+
+        assert!(matches!(
+            limiter.check(3),
+            Err(RateLimitError::GuildDailyLimit(_))
+        ));
+    }
+
+    #[test]
+    fn usage_is_sorted_by_count() {
+        let limiter = limiter(0, None, None);
+        limiter.check(1).unwrap().commit();
+        limiter.check(2).unwrap().commit();
+        limiter.check(2).unwrap().commit();
+
+        let usage = limiter.usage();
+        assert_eq!(usage.total, 3);
+        assert_eq!(usage.users, vec![(2, 2), (1, 1)]);
+    }
+}
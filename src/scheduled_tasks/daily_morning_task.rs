@@ -6,17 +6,19 @@ use rand::seq::SliceRandom;
 use serenity::builder::{CreateEmbed, CreateMessage};
 use serenity::model::id::ChannelId;
 use serenity::prelude::*;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 
 use super::ScheduledTask;
-use nidaime_takohachi::utils;
+use nidaime_takohachi::llm::LlmProvider;
+use nidaime_takohachi::{utils, weather};
 
 pub struct DailyMorningTask {
     channel_id: ChannelId,
     hour: u32,
     minute: u32,
-    gemini_api_key: Option<String>,
+    llm: Option<Arc<dyn LlmProvider>>,
 }
 
 impl DailyMorningTask {
@@ -25,12 +27,13 @@ impl DailyMorningTask {
             channel_id: ChannelId::new(channel_id),
             hour: 7,
             minute: 0,
-            gemini_api_key: None,
+            llm: None,
         }
     }
 
-    pub fn with_api_key(mut self, api_key: String) -> Self {
-        self.gemini_api_key = Some(api_key);
+    /// 雑学の生成に使う LLM を設定する
+    pub fn with_llm(mut self, llm: Arc<dyn LlmProvider>) -> Self {
+        self.llm = Some(llm);
         self
     }
 
@@ -65,26 +68,31 @@ impl DailyMorningTask {
 
         let what_today = utils::get_what_today(month, day).await;
 
-        let tokyo_weather = match utils::get_weather("130010").await {
-            Ok(weather_info) => weather_info,
+        let tokyo_weather = match weather::fetch_forecast("130010").await {
+            Ok(forecast) => weather::render::summary(&forecast),
             Err(e) => format!("東京の天気情報を取得できませんでした: {}", e),
         };
-        let yamagata_weather = match utils::get_weather("060010").await {
-            Ok(weather_info) => weather_info,
+        let yamagata_weather = match weather::fetch_forecast("060010").await {
+            Ok(forecast) => weather::render::summary(&forecast),
             Err(e) => format!("山形の天気情報を取得できませんでした: {}", e),
         };
 
-        let trivia = if let Some(api_key) = &self.gemini_api_key {
-            match utils::get_trivia(api_key).await {
+        let trivia = if let Some(llm) = &self.llm {
+            match utils::get_trivia(llm.as_ref()).await {
                 Ok(t) => t,
                 Err(e) => {
-                    eprintln!("[{}] Gemini API error: {}", self.name(), e);
+                    eprintln!("[{}] {} error: {}", self.name(), llm.service_name(), e);
                     "今日の雑学: 知識は力なり！".to_string()
                 }
             }
         } else {
             "今日の雑学: APIキーが設定されていません".to_string()
         };
+        let powered_by = self
+            .llm
+            .as_ref()
+            .map(|llm| format!("\n(Powered by {})", llm.model()))
+            .unwrap_or_default();
 
         let market_data = vec![
             ("USDJPY=X", "USD/JPY", "円", "💰"),
@@ -118,8 +126,8 @@ impl DailyMorningTask {
         };
 
         format!(
-            "### 💡 今日はなんの日？\n{}\n\n### 📚 今日の雑学\n{}\n(Powered by [Gemini](https://ai.google.dev/gemini-api/docs/models))\n\n### 💹 相場\n{}\n\n### ⛅ 今日の天気\n{}\n{}",
-            what_today, trivia, market_text, tokyo_weather, yamagata_weather
+            "### 💡 今日はなんの日？\n{}\n\n### 📚 今日の雑学\n{}{}\n\n### 💹 相場\n{}\n\n### ⛅ 今日の天気\n{}\n{}",
+            what_today, trivia, powered_by, market_text, tokyo_weather, yamagata_weather
         )
     }
 }
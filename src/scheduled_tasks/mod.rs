@@ -1,16 +1,21 @@
 pub mod daily_morning_task;
 pub mod delete_message;
+pub mod weather_warning_task;
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use serenity::prelude::*;
 use tokio::time::sleep;
 
-use crate::config::ScheduledTasksConfig;
+use crate::config::{Config, GeminiConfig};
+use nidaime_takohachi::gemini::GeminiClient;
+use nidaime_takohachi::llm::LlmProvider;
 
 pub use daily_morning_task::DailyMorningTask;
 pub use delete_message::DeleteMessageTask;
+pub use weather_warning_task::WeatherWarningTask;
 
 /// スケジュールタスクのトレイト
 #[async_trait]
@@ -56,7 +61,8 @@ pub async fn start_scheduled_tasks(ctx: Context, tasks: Vec<Box<dyn ScheduledTas
 }
 
 /// 設定に基づいてスケジュールタスクを作成する関数
-pub fn create_scheduled_tasks(config: &ScheduledTasksConfig) -> Vec<Box<dyn ScheduledTask>> {
+pub fn create_scheduled_tasks(root_config: &Config) -> Vec<Box<dyn ScheduledTask>> {
+    let config = &root_config.scheduled_tasks;
     let mut tasks: Vec<Box<dyn ScheduledTask>> = Vec::new();
 
     // 自動メッセージ削除タスクを追加
@@ -75,8 +81,14 @@ pub fn create_scheduled_tasks(config: &ScheduledTasksConfig) -> Vec<Box<dyn Sche
         && morning_task_config.enabled
     {
         let mut task = DailyMorningTask::new(morning_task_config.channel_id);
-        if let Some(api_key) = &morning_task_config.gemini_api_key {
-            task = task.with_api_key(api_key.clone());
+        // タスクに `gemini_api_key` があればそのキーの Gemini を使い、なければ `gemini` で選んだ LLM を使う
+        let llm = morning_task_config
+            .gemini_api_key
+            .as_deref()
+            .map(|api_key| Arc::new(GeminiClient::new(api_key)) as Arc<dyn LlmProvider>)
+            .or_else(|| root_config.gemini.as_ref().map(GeminiConfig::provider));
+        if let Some(llm) = llm {
+            task = task.with_llm(llm);
         }
         tasks.push(Box::new(task));
         println!(
@@ -85,6 +97,19 @@ pub fn create_scheduled_tasks(config: &ScheduledTasksConfig) -> Vec<Box<dyn Sche
         );
     }
 
+    // 気象警報・注意報の発表・解除の投稿を追加
+    if let Some(warning_task_config) = &config.weather_warning_task
+        && warning_task_config.enabled
+        && !warning_task_config.areas.is_empty()
+    {
+        tasks.push(Box::new(WeatherWarningTask::new(warning_task_config)));
+        println!(
+            "WeatherWarningTask has been enabled for {} areas in channel {}",
+            warning_task_config.areas.len(),
+            warning_task_config.channel_id
+        );
+    }
+
     if !tasks.is_empty() {
         println!("Total {} scheduled tasks created", tasks.len());
     }
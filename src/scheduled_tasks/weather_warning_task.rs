@@ -0,0 +1,293 @@
+use std::collections::{BTreeSet, HashMap};
+
+use async_trait::async_trait;
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, CreateMessage};
+use serenity::model::Colour;
+use serenity::model::id::ChannelId;
+use serenity::prelude::*;
+
+use super::ScheduledTask;
+use crate::config::{WarningAreaConfig, WeatherWarningTaskConfig};
+use nidaime_takohachi::json_store::JsonFileStore;
+use nidaime_takohachi::weather::jma::{self, Warnings};
+
+const DEFAULT_INTERVAL_SECS: u64 = 600;
+const DEFAULT_STATE_PATH: &str = "weather_warnings.json";
+
+/// 区域ごとの発表中の警報・注意報コード
+type WarningState = HashMap<String, BTreeSet<String>>;
+
+/// 設定した区域に警報・注意報が発表・解除されたらチャンネルに投稿するタスク
+///
+/// 投稿済みの警報・注意報はファイルに保存し、同じものを何度も投稿しないようにする（再起動しても残る）。
+pub struct WeatherWarningTask {
+    channel_id: ChannelId,
+    areas: Vec<WarningAreaConfig>,
+    interval_secs: u64,
+    state: JsonFileStore<WarningState>,
+}
+
+/// 1 区域分の変化
+struct AreaChange<'a> {
+    area: &'a WarningAreaConfig,
+    issued: Vec<String>,
+    lifted: Vec<String>,
+}
+
+impl WeatherWarningTask {
+    pub fn new(config: &WeatherWarningTaskConfig) -> Self {
+        let state_path = config
+            .state_path
+            .clone()
+            .unwrap_or_else(|| DEFAULT_STATE_PATH.to_string());
+        let state = JsonFileStore::load(&state_path).unwrap_or_else(|e| {
+            eprintln!(
+                "[WeatherWarningTask] Failed to load weather warnings from {state_path}, starting empty: {e}"
+            );
+            JsonFileStore::new(&state_path)
+        });
+        Self {
+            channel_id: ChannelId::new(config.channel_id),
+            areas: config.areas.clone(),
+            interval_secs: config.interval_secs.unwrap_or(DEFAULT_INTERVAL_SECS),
+            state,
+        }
+    }
+}
+
+/// 前回の記録から発表・解除された警報・注意報と、次に記録する状態を求める
+fn diff<'a>(
+    areas: &'a [WarningAreaConfig],
+    previous: &WarningState,
+    warnings_by_office: &HashMap<&str, Warnings>,
+) -> (Vec<AreaChange<'a>>, WarningState) {
+    let mut next = previous.clone();
+    let mut changes = Vec::new();
+    for area in areas {
+        // 取得できなかった府県予報区は、解除されたと誤って投稿しないように飛ばす
+        let Some(warnings) = warnings_by_office.get(area.office_code.as_str()) else {
+            continue;
+        };
+        let active: BTreeSet<String> = warnings
+            .area(&area.area_code)
+            .map(|a| a.active().map(|w| w.code.clone()).collect())
+            .unwrap_or_default();
+        let before = next.remove(&area.area_code).unwrap_or_default();
+
+        let issued: Vec<String> = active.difference(&before).cloned().collect();
+        let lifted: Vec<String> = before.difference(&active).cloned().collect();
+        if !issued.is_empty() || !lifted.is_empty() {
+            changes.push(AreaChange {
+                area,
+                issued,
+                lifted,
+            });
+        }
+        if !active.is_empty() {
+            next.insert(area.area_code.clone(), active);
+        }
+    }
+    (changes, next)
+}
+
+#[async_trait]
+impl ScheduledTask for WeatherWarningTask {
+    fn name(&self) -> &str {
+        "WeatherWarningTask"
+    }
+
+    fn interval_secs(&self) -> u64 {
+        self.interval_secs
+    }
+
+    async fn execute(&self, ctx: &Context) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // 同じ府県予報区の区域はまとめて 1 回だけ取得する
+        let mut warnings_by_office: HashMap<&str, Warnings> = HashMap::new();
+        for area in &self.areas {
+            let office_code = area.office_code.as_str();
+            if warnings_by_office.contains_key(office_code) {
+                continue;
+            }
+            match jma::fetch_warnings(office_code).await {
+                Ok(warnings) => {
+                    warnings_by_office.insert(office_code, warnings);
+                }
+                Err(e) => eprintln!(
+                    "[{}] Failed to fetch warnings for office {}: {}",
+                    self.name(),
+                    office_code,
+                    e
+                ),
+            }
+        }
+
+        let (changes, next_state) = self
+            .state
+            .read(|state| diff(&self.areas, state, &warnings_by_office));
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = Vec::new();
+        for change in &changes {
+            let name = change
+                .area
+                .name
+                .as_deref()
+                .unwrap_or(&change.area.area_code);
+            if !change.issued.is_empty() {
+                lines.push(format!(
+                    "🚨 **{}**: {} が発表されました",
+                    name,
+                    warning_names(&change.issued)
+                ));
+            }
+            if !change.lifted.is_empty() {
+                lines.push(format!(
+                    "✅ **{}**: {} が解除されました",
+                    name,
+                    warning_names(&change.lifted)
+                ));
+            }
+        }
+
+        // 新たに発表されたもののうち最も重いものに合わせて色を変える
+        let colour = match changes
+            .iter()
+            .flat_map(|change| &change.issued)
+            .map(|code| jma::warning_level(code))
+            .max()
+        {
+            Some(2) => Colour::PURPLE,
+            Some(1) => Colour::RED,
+            Some(_) => Colour::GOLD,
+            None => Colour::DARK_GREEN,
+        };
+
+        let mut embed = CreateEmbed::new()
+            .title("⚠️ 気象警報・注意報")
+            .description(lines.join("\n"))
+            .colour(colour)
+            .footer(CreateEmbedFooter::new("気象庁"));
+        let headlines: Vec<&str> = changes
+            .iter()
+            .filter_map(|change| warnings_by_office.get(change.area.office_code.as_str()))
+            .map(|warnings| warnings.headline.trim())
+            .filter(|headline| !headline.is_empty())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if !headlines.is_empty() {
+            embed = embed.field("概況", headlines.join("\n"), false);
+        }
+
+        self.channel_id
+            .send_message(&ctx.http, CreateMessage::new().embed(embed))
+            .await?;
+        // 投稿できてから記録する（投稿に失敗したら次の確認でもう一度投稿する）
+        self.state.update(|state| *state = next_state).await?;
+
+        println!(
+            "[{}] Posted {} warning changes to channel {}",
+            self.name(),
+            changes.len(),
+            self.channel_id
+        );
+        Ok(())
+    }
+}
+
+fn warning_names(codes: &[String]) -> String {
+    codes
+        .iter()
+        .map(|code| jma::warning_name(code))
+        .collect::<Vec<_>>()
+        .join("、")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nidaime_takohachi::weather::jma::{AreaWarnings, Warning};
+
+    fn area(office_code: &str, area_code: &str) -> WarningAreaConfig {
+        WarningAreaConfig {
+            office_code: office_code.to_string(),
+            area_code: area_code.to_string(),
+            name: None,
+        }
+    }
+
+    fn warnings(area_code: &str, codes: &[&str]) -> Warnings {
+        Warnings {
+            report_datetime: String::new(),
+            headline: String::new(),
+            areas: vec![AreaWarnings {
+                code: area_code.to_string(),
+                warnings: codes
+                    .iter()
+                    .map(|code| Warning {
+                        code: code.to_string(),
+                        status: "発表".to_string(),
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    fn state(area_code: &str, codes: &[&str]) -> WarningState {
+        HashMap::from([(
+            area_code.to_string(),
+            codes.iter().map(|code| code.to_string()).collect(),
+        )])
+    }
+
+    #[test]
+    fn reports_issued_and_lifted_warnings() {
+        let areas = [area("130000", "130010")];
+        let previous = state("130010", &["10", "14"]);
+        let fetched = HashMap::from([("130000", warnings("130010", &["03", "14"]))]);
+
+        let (changes, next) = diff(&areas, &previous, &fetched);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].issued, ["03"]);
+        assert_eq!(changes[0].lifted, ["10"]);
+        assert_eq!(next, state("130010", &["03", "14"]));
+    }
+
+    #[test]
+    fn unchanged_warnings_are_not_reported() {
+        let areas = [area("130000", "130010")];
+        let previous = state("130010", &["03"]);
+        let fetched = HashMap::from([("130000", warnings("130010", &["03"]))]);
+
+        let (changes, next) = diff(&areas, &previous, &fetched);
+
+        assert!(changes.is_empty());
+        assert_eq!(next, previous);
+    }
+
+    #[test]
+    fn all_lifted_area_is_removed_from_state() {
+        let areas = [area("130000", "130010")];
+        let previous = state("130010", &["03"]);
+        let fetched = HashMap::from([("130000", warnings("130010", &[]))]);
+
+        let (changes, next) = diff(&areas, &previous, &fetched);
+
+        assert_eq!(changes[0].lifted, ["03"]);
+        assert!(next.is_empty());
+    }
+
+    #[test]
+    fn offices_that_failed_to_fetch_are_skipped() {
+        let areas = [area("130000", "130010")];
+        let previous = state("130010", &["03"]);
+
+        let (changes, next) = diff(&areas, &previous, &HashMap::new());
+
+        assert!(changes.is_empty());
+        assert_eq!(next, previous);
+    }
+}
@@ -0,0 +1,104 @@
+/// Server-Sent Events のレスポンスを 1 イベントずつ読み出す
+pub struct SseReader {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl SseReader {
+    pub fn new(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            buffer: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// 次のイベントの `data:` の中身を返す。ストリームが終わったら `None` を返す
+    ///
+    /// `data:` 行を持たないイベント（コメントや keep-alive）は読み飛ばす。
+    pub async fn next_event(&mut self) -> Option<Result<String, reqwest::Error>> {
+        loop {
+            if let Some(data) = take_event(&mut self.buffer) {
+                if data.is_empty() {
+                    continue;
+                }
+                return Some(Ok(data));
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            match self.response.chunk().await {
+                // CRLF 区切りのサーバーにも対応できるよう、CR は読み捨てる
+                Ok(Some(bytes)) => self.buffer.extend(bytes.iter().filter(|&&b| b != b'\r')),
+                Ok(None) => {
+                    self.finished = true;
+                    // 末尾が空行で終わっていなくても残りを 1 イベントとして扱う
+                    if !self.buffer.is_empty() {
+                        self.buffer.extend_from_slice(b"\n\n");
+                    }
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// バッファから空行で区切られたイベントを 1 つ取り出し、`data:` 行の中身を返す
+///
+/// 複数の `data:` 行は改行でつなぐ。`data:` 行がなければ空文字列を返す。
+fn take_event(buffer: &mut Vec<u8>) -> Option<String> {
+    let end = buffer.windows(2).position(|w| w == b"\n\n")?;
+    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+    let event = String::from_utf8_lossy(&event);
+
+    let data = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_one_event_at_a_time() {
+        let mut buffer = b"data: {\"a\":1}\n\ndata: {\"b\":2}\n\n".to_vec();
+
+        assert_eq!(take_event(&mut buffer).as_deref(), Some("{\"a\":1}"));
+        assert_eq!(take_event(&mut buffer).as_deref(), Some("{\"b\":2}"));
+        assert_eq!(take_event(&mut buffer), None);
+    }
+
+    #[test]
+    fn waits_for_the_blank_line() {
+        let mut buffer = b"data: partial".to_vec();
+
+        assert_eq!(take_event(&mut buffer), None);
+        assert_eq!(buffer, b"data: partial");
+    }
+
+    #[test]
+    fn joins_multiple_data_lines() {
+        let mut buffer = b"event: message\ndata: first\ndata:second\n\n".to_vec();
+
+        assert_eq!(take_event(&mut buffer).as_deref(), Some("first\nsecond"));
+    }
+
+    #[test]
+    fn event_without_data_is_empty() {
+        let mut buffer = b": keep-alive\n\n".to_vec();
+
+        assert_eq!(take_event(&mut buffer).as_deref(), Some(""));
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use crate::json_store::JsonFileStore;
+
+/// 項目ごとの表示回数を JSON ファイルに保存する（`/dic top` と入力補完の並び順に使う）
+pub struct HitCounter {
+    file: JsonFileStore<HashMap<String, u32>>,
+}
+
+impl HitCounter {
+    /// 空の状態で作成する（記録するまでファイルには触れない）
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: JsonFileStore::new(path),
+        }
+    }
+
+    /// ファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            file: JsonFileStore::load(path)?,
+        })
+    }
+
+    pub fn get(&self, trigger: &str) -> u32 {
+        self.file
+            .read(|counts| counts.get(trigger).copied().unwrap_or(0))
+    }
+
+    /// 表示回数を 1 増やして保存する
+    pub async fn record(&self, trigger: &str) -> io::Result<()> {
+        self.file
+            .update(|counts| *counts.entry(trigger.to_string()).or_default() += 1)
+            .await
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::{BoxError, TriggerFields, TriggerStore, TriggerTable};
+use crate::json_store::write_atomically;
+
+/// ローカルの JSON ファイルに保存する辞書
+///
+/// ファイルは `TriggerFields` の配列。Google のサービスアカウントを用意できない小さな環境や
+/// 動作確認のために使う。ファイルが存在しなければ空の辞書として扱い、最初の書き込みで作成する。
+pub struct LocalTriggerStore {
+    path: PathBuf,
+    table: Mutex<Option<Arc<TriggerTable>>>,
+    /// 読み込みから書き込みまでの間に別の書き込みが割り込まないようにする
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl LocalTriggerStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            table: Mutex::new(None),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// ファイルのすべての項目を読み込む
+    pub async fn load(&self) -> Result<Vec<TriggerFields>, BoxError> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 一時ファイルに書いてから置き換え、書き込み途中で止まってもファイルが壊れないようにする
+    async fn save(&self, entries: &[TriggerFields]) -> Result<(), BoxError> {
+        let json = serde_json::to_string_pretty(entries)?;
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || write_atomically(&path, &json)).await??;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TriggerStore for LocalTriggerStore {
+    fn service_name(&self) -> &'static str {
+        "辞書ファイル"
+    }
+
+    fn source_name(&self) -> &'static str {
+        "辞書ファイル"
+    }
+
+    async fn table(&self) -> Result<Arc<TriggerTable>, BoxError> {
+        let cached = self.table.lock().unwrap().clone();
+        match cached {
+            Some(table) => Ok(table),
+            None => self.refresh().await,
+        }
+    }
+
+    async fn refresh(&self) -> Result<Arc<TriggerTable>, BoxError> {
+        let entries = self
+            .load()
+            .await?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, fields)| fields.to_entry(i + 1))
+            .collect();
+        let table = Arc::new(TriggerTable { entries });
+        *self.table.lock().unwrap() = Some(table.clone());
+        Ok(table)
+    }
+
+    async fn append(&self, fields: &TriggerFields) -> Result<(), BoxError> {
+        let _guard = self.write_lock.lock().await;
+        let mut entries = self.load().await?;
+        entries.push(fields.clone());
+        self.save(&entries).await?;
+        self.refresh().await?;
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        row: usize,
+        expected_trigger: &str,
+        fields: &TriggerFields,
+    ) -> Result<(), BoxError> {
+        let _guard = self.write_lock.lock().await;
+        let mut entries = self.load().await?;
+        let Some(entry) = row.checked_sub(1).and_then(|i| entries.get_mut(i)) else {
+            return Err(format!("entry {} does not exist", row).into());
+        };
+        if entry.trigger.trim() != expected_trigger {
+            return Err(format!(
+                "entry {} has changed (expected trigger {:?}, found {:?})",
+                row, expected_trigger, entry.trigger
+            )
+            .into());
+        }
+        *entry = fields.clone();
+        self.save(&entries).await?;
+        self.refresh().await?;
+        Ok(())
+    }
+
+    async fn replace_all(&self, entries: &[TriggerFields]) -> Result<(), BoxError> {
+        let _guard = self.write_lock.lock().await;
+        self.save(entries).await?;
+        self.refresh().await?;
+        Ok(())
+    }
+}
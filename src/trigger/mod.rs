@@ -0,0 +1,367 @@
+pub mod hits;
+pub mod local;
+pub mod sheet;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::normalize::{edit_distance, normalize_keyword};
+
+pub use hits::HitCounter;
+pub use local::LocalTriggerStore;
+pub use sheet::TriggerSheet;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 文中からキーワードを探すときに対象にするキーワードの最小文字数（短すぎると誤反応が多いため）
+const MIN_TEXT_KEYWORD_CHARS: usize = 2;
+
+/// `/dic` で表示する辞書の保存先（Google スプレッドシートやローカルのファイル）
+#[async_trait]
+pub trait TriggerStore: Send + Sync {
+    /// エラーメッセージに表示する保存先の名前
+    fn service_name(&self) -> &'static str;
+
+    /// `/dic refresh` の説明や応答に表示する、読み込み元の名前（「スプレッドシート」など）
+    fn source_name(&self) -> &'static str;
+
+    /// トリガー表を返す（キャッシュがあればそれを返す）
+    async fn table(&self) -> Result<Arc<TriggerTable>, BoxError>;
+
+    /// キャッシュを無視してトリガー表を読み込み直す
+    async fn refresh(&self) -> Result<Arc<TriggerTable>, BoxError>;
+
+    /// 新しいエントリを末尾に追加する
+    async fn append(&self, fields: &TriggerFields) -> Result<(), BoxError>;
+
+    /// `row` 番目のエントリを書き換える
+    ///
+    /// 書き込む直前にそのエントリの `trigger` が `expected_trigger` のままであることを確かめ、
+    /// 変わっていればエラーにする。並べ替えなどで別のエントリを上書きしないようにするため。
+    async fn update(
+        &self,
+        row: usize,
+        expected_trigger: &str,
+        fields: &TriggerFields,
+    ) -> Result<(), BoxError>;
+
+    /// すべてのエントリを置き換える（保存先どうしの同期用）
+    async fn replace_all(&self, entries: &[TriggerFields]) -> Result<(), BoxError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct TriggerEntry {
+    /// 保存先での位置（スプレッドシートなら行番号、ローカルのファイルなら何番目か。1 始まり）
+    pub row: usize,
+    /// 検索に使うキーワード（trigger, alias01, alias02, … の順）
+    pub keywords: Vec<String>,
+    /// `tags` 列の値（カンマ区切り）。タグで検索すると同じタグのエントリがすべて見つかる
+    pub tags: Vec<String>,
+    pub response: String,
+    pub title: String,
+    pub description: String,
+    pub thumbnail_url: String,
+    pub image_url: String,
+    /// 埋め込みの色（`color` 列。`#RRGGBB` 形式）
+    pub color: Option<u32>,
+    /// 埋め込みのタイトルのリンク先（`url` 列）
+    pub url: String,
+    /// 埋め込みのフッター（`footer` 列）
+    pub footer: String,
+}
+
+impl TriggerEntry {
+    /// キーワードのいずれかと一致するか（全角・半角やカタカナ・ひらがなの違いは無視する）
+    pub fn matches(&self, keyword: &str) -> bool {
+        let keyword = normalize_keyword(keyword);
+        self.keywords
+            .iter()
+            .any(|k| normalize_keyword(k) == keyword)
+    }
+
+    /// タグのいずれかと一致するか
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_keyword(tag);
+        self.tags.iter().any(|t| normalize_keyword(t) == tag)
+    }
+
+    /// 一覧などに表示する名前（最初のキーワード）
+    pub fn trigger(&self) -> &str {
+        self.keywords
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+/// 一致するキーワードがなかったときの候補
+#[derive(Debug, Clone)]
+pub struct Suggestion<'a> {
+    /// 入力に近かったキーワード
+    pub keyword: &'a str,
+    pub entry: &'a TriggerEntry,
+    /// 正規化したキーワードとの編集距離
+    pub distance: usize,
+}
+
+/// 保存先に書き込む 1 件分の項目（ローカルのファイルにはこの形で保存する）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerFields {
+    pub trigger: String,
+    /// `aliasNN` 列に順に書き込む別名
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub response: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub thumbnail_url: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub image_url: String,
+    /// `#RRGGBB` 形式の色
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub color: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub footer: String,
+}
+
+impl TriggerFields {
+    /// 検索用のエントリにする（キーワードが 1 つもなければ `None`）
+    pub fn to_entry(&self, row: usize) -> Option<TriggerEntry> {
+        let keywords: Vec<String> = std::iter::once(&self.trigger)
+            .chain(&self.aliases)
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        if keywords.is_empty() {
+            return None;
+        }
+
+        Some(TriggerEntry {
+            row,
+            keywords,
+            tags: self
+                .tags
+                .iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            response: self.response.trim().to_string(),
+            title: self.title.trim().to_string(),
+            description: self.description.trim().to_string(),
+            thumbnail_url: self.thumbnail_url.trim().to_string(),
+            image_url: self.image_url.trim().to_string(),
+            color: parse_color(&self.color),
+            url: self.url.trim().to_string(),
+            footer: self.footer.trim().to_string(),
+        })
+    }
+}
+
+impl From<&TriggerEntry> for TriggerFields {
+    fn from(entry: &TriggerEntry) -> Self {
+        Self {
+            trigger: entry.trigger().to_string(),
+            aliases: entry.keywords.iter().skip(1).cloned().collect(),
+            tags: entry.tags.clone(),
+            title: entry.title.clone(),
+            description: entry.description.clone(),
+            response: entry.response.clone(),
+            thumbnail_url: entry.thumbnail_url.clone(),
+            image_url: entry.image_url.clone(),
+            color: entry
+                .color
+                .map(|color| format!("#{:06X}", color))
+                .unwrap_or_default(),
+            url: entry.url.clone(),
+            footer: entry.footer.clone(),
+        }
+    }
+}
+
+/// `trigger` シート（またはローカルのファイル）を読み込んだもの
+#[derive(Debug, Clone, Default)]
+pub struct TriggerTable {
+    pub entries: Vec<TriggerEntry>,
+}
+
+impl TriggerTable {
+    pub fn find(&self, keyword: &str) -> Option<&TriggerEntry> {
+        self.entries.iter().find(|entry| entry.matches(keyword))
+    }
+
+    /// キーワードかタグが一致するエントリをすべて返す（キーワードが一致したものが先）
+    pub fn find_all(&self, keyword: &str) -> Vec<&TriggerEntry> {
+        let by_keyword = self.entries.iter().filter(|entry| entry.matches(keyword));
+        let by_tag = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.matches(keyword) && entry.has_tag(keyword));
+        by_keyword.chain(by_tag).collect()
+    }
+
+    /// 文中に含まれるキーワードを探し、最も長く一致したエントリとキーワードを返す
+    ///
+    /// 英数字で始まる（終わる）キーワードは、前（後ろ）が英数字でない位置でだけ一致とみなす
+    /// （`gomi` が `gomibako` に反応しないように）。日本語には単語の区切りがないので部分一致で探す。
+    pub fn find_in_text(&self, text: &str) -> Option<(&TriggerEntry, &str)> {
+        let text = normalize_keyword(text);
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.keywords.iter().map(move |k| (entry, k)))
+            .filter_map(|(entry, keyword)| {
+                let normalized = normalize_keyword(keyword);
+                let length = normalized.chars().count();
+                (length >= MIN_TEXT_KEYWORD_CHARS && contains_word(&text, &normalized)).then_some((
+                    length,
+                    entry,
+                    keyword.as_str(),
+                ))
+            })
+            .max_by_key(|&(length, ..)| length)
+            .map(|(_, entry, keyword)| (entry, keyword))
+    }
+
+    /// 入力に近いキーワードを近い順に最大 `limit` 件返す
+    ///
+    /// 編集距離がキーワードの長さのおよそ 3 分の 1 以内のものか、
+    /// 入力を含む（または入力に含まれる）ものを候補にする。1 つのエントリからは 1 件だけ返す。
+    pub fn suggest(&self, keyword: &str, limit: usize) -> Vec<Suggestion<'_>> {
+        let query = normalize_keyword(keyword);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut suggestions: Vec<Suggestion<'_>> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .keywords
+                    .iter()
+                    .filter_map(|k| {
+                        let normalized = normalize_keyword(k);
+                        let distance = edit_distance(&query, &normalized);
+                        let threshold =
+                            (normalized.chars().count().max(query.chars().count()) / 3).max(1);
+                        let contains = normalized.contains(&query) || query.contains(&normalized);
+                        (distance <= threshold || contains).then_some(Suggestion {
+                            keyword: k.as_str(),
+                            entry,
+                            distance,
+                        })
+                    })
+                    .min_by_key(|s| s.distance)
+            })
+            .collect();
+        suggestions.sort_by_key(|s| s.distance);
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+/// `from` のエントリをすべて `to` に書き写す（`to` の既存のエントリは消える）
+///
+/// 書き写した件数を返す。
+pub async fn sync(from: &dyn TriggerStore, to: &dyn TriggerStore) -> Result<usize, BoxError> {
+    let table = from.refresh().await?;
+    let entries: Vec<TriggerFields> = table.entries.iter().map(TriggerFields::from).collect();
+    to.replace_all(&entries).await?;
+    Ok(entries.len())
+}
+
+/// `text` に `word` が単語として含まれるか（英数字の途中で始まったり終わったりするものは除く）
+fn contains_word(text: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let starts_with_word_char = word.chars().next().is_some_and(is_word_char);
+    let ends_with_word_char = word.chars().next_back().is_some_and(is_word_char);
+
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !(starts_with_word_char && before.is_some_and(is_word_char)
+            || ends_with_word_char && after.is_some_and(is_word_char))
+    })
+}
+
+/// `#RRGGBB` か `RRGGBB` 形式の色を読み取る
+fn parse_color(text: &str) -> Option<u32> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(keywords: &[&[&str]]) -> TriggerTable {
+        let entries = keywords
+            .iter()
+            .enumerate()
+            .filter_map(|(i, keywords)| {
+                TriggerFields {
+                    trigger: keywords[0].to_string(),
+                    aliases: keywords[1..].iter().map(|k| k.to_string()).collect(),
+                    ..Default::default()
+                }
+                .to_entry(i + 1)
+            })
+            .collect();
+        TriggerTable { entries }
+    }
+
+    #[test]
+    fn finds_keyword_in_text() {
+        let table = table(&[&["genkai", "限界"], &["gomi"]]);
+
+        let (entry, keyword) = table.find_in_text("今日はもう限界です").unwrap();
+        assert_eq!((entry.trigger(), keyword), ("genkai", "限界"));
+        let (entry, _) = table.find_in_text("それは gomi だね").unwrap();
+        assert_eq!(entry.trigger(), "gomi");
+    }
+
+    #[test]
+    fn ignores_alphanumeric_keyword_inside_a_word() {
+        let table = table(&[&["gomi"]]);
+
+        assert!(table.find_in_text("gomibako を買った").is_none());
+        assert!(table.find_in_text("ecogomi").is_none());
+        assert!(table.find_in_text("gomi、捨てといて").is_some());
+    }
+
+    #[test]
+    fn prefers_the_longest_match() {
+        let table = table(&[&["徳井"], &["徳井病"]]);
+
+        let (entry, _) = table.find_in_text("完全に徳井病だわ").unwrap();
+        assert_eq!(entry.trigger(), "徳井病");
+    }
+
+    #[test]
+    fn ignores_single_character_keywords() {
+        let table = table(&[&["草"]]);
+
+        assert!(table.find_in_text("草生える").is_none());
+    }
+
+    #[test]
+    fn matches_regardless_of_width_and_kana() {
+        let table = table(&[&["ゲンカイ"]]);
+
+        assert!(table.find_in_text("もうｹﾞﾝｶｲ").is_some());
+        assert!(table.find_in_text("もうげんかい").is_some());
+    }
+}
@@ -0,0 +1,324 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::{BoxError, TriggerEntry, TriggerFields, TriggerStore, TriggerTable};
+use crate::google_sheets::{SheetRow, SheetsClient};
+
+/// トリガー表を再取得するまでの既定の時間
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+/// トリガー表のシート名
+const SHEET_NAME: &str = "trigger";
+/// ヘッダーの行番号（1 始まり）
+const HEADER_ROW: usize = 2;
+
+struct CachedTable {
+    table: Arc<TriggerTable>,
+    fetched_at: Instant,
+}
+
+/// スプレッドシートの `trigger` シートを読み込み、メモリ上にキャッシュする
+///
+/// キャッシュが古くなった後は、古い内容を返しつつバックグラウンドで取り直す
+/// （stale-while-revalidate）。取り直しに失敗しても古い内容を返し続けるので、
+/// Google に一時的に繋がらなくても `/dic` は応答できる。
+///
+/// `clone()` したものはキャッシュを共有する。
+#[derive(Clone)]
+pub struct TriggerSheet {
+    client: SheetsClient,
+    ttl: Duration,
+    table: Arc<Mutex<Option<CachedTable>>>,
+    refreshing: Arc<AtomicBool>,
+}
+
+impl TriggerSheet {
+    pub fn new(client: SheetsClient) -> Self {
+        Self {
+            client,
+            ttl: DEFAULT_CACHE_TTL,
+            table: Arc::new(Mutex::new(None)),
+            refreshing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// キーワードに一致するエントリを探す
+    pub async fn search(&self, keyword: &str) -> Result<Option<TriggerEntry>, BoxError> {
+        let table = self.table().await?;
+        Ok(table.find(keyword).cloned())
+    }
+
+    /// バックグラウンドでトリガー表を取り直す（すでに取り直し中なら何もしない）
+    fn spawn_refresh(&self) {
+        if self.refreshing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let sheet = self.clone();
+        tokio::spawn(async move {
+            // 取り直しが失敗・panic しても、次の取り直しができるように必ず戻す
+            let _guard = RefreshGuard(sheet.refreshing.clone());
+            if let Err(e) = sheet.refresh().await {
+                eprintln!("Failed to refresh trigger sheet, serving stale data: {}", e);
+            }
+        });
+    }
+
+    /// 最新のヘッダー行を読む（読み込み時の `SheetTable` と同じく列名の前後の空白は取り除く）
+    async fn header(&self) -> Result<Vec<String>, BoxError> {
+        let header = self.client.get_row(SHEET_NAME, HEADER_ROW).await?;
+        Ok(trim_header(header))
+    }
+
+    async fn fetch_table(&self) -> Result<TriggerTable, BoxError> {
+        let sheet = self.client.get_table(SHEET_NAME, HEADER_ROW).await?;
+        let entries = sheet
+            .rows
+            .iter()
+            .filter_map(|row| row_to_fields(&sheet.header, row).to_entry(row.number))
+            .collect();
+        Ok(TriggerTable { entries })
+    }
+}
+
+/// drop されたときに取り直し中のフラグを下ろす
+struct RefreshGuard(Arc<AtomicBool>);
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl TriggerStore for TriggerSheet {
+    fn service_name(&self) -> &'static str {
+        "Google Sheets"
+    }
+
+    fn source_name(&self) -> &'static str {
+        "スプレッドシート"
+    }
+
+    /// トリガー表を返す
+    ///
+    /// キャッシュがなければ取得するまで待つ。キャッシュが古ければそのまま返し、裏で取り直す。
+    async fn table(&self) -> Result<Arc<TriggerTable>, BoxError> {
+        let cached = {
+            let cache = self.table.lock().unwrap();
+            cache
+                .as_ref()
+                .map(|c| (c.table.clone(), c.fetched_at.elapsed() < self.ttl))
+        };
+
+        match cached {
+            Some((table, true)) => Ok(table),
+            Some((table, false)) => {
+                self.spawn_refresh();
+                Ok(table)
+            }
+            None => self.refresh().await,
+        }
+    }
+
+    async fn refresh(&self) -> Result<Arc<TriggerTable>, BoxError> {
+        let table = Arc::new(self.fetch_table().await?);
+        *self.table.lock().unwrap() = Some(CachedTable {
+            table: table.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(table)
+    }
+
+    /// 新しいエントリを表の末尾に追加する
+    async fn append(&self, fields: &TriggerFields) -> Result<(), BoxError> {
+        // 列の並びが変わっている可能性があるので、最新のヘッダーに合わせて書き込む
+        let header = self.header().await?;
+        let mut row = vec![String::new(); header.len()];
+        fill_row(fields, &header, &mut row)?;
+
+        self.client.append(SHEET_NAME, &[row]).await?;
+
+        self.refresh().await?;
+        Ok(())
+    }
+
+    /// 既存のエントリを書き換える
+    async fn update(
+        &self,
+        row_number: usize,
+        expected_trigger: &str,
+        fields: &TriggerFields,
+    ) -> Result<(), BoxError> {
+        let header = self.header().await?;
+        let mut row = self.client.get_row(SHEET_NAME, row_number).await?;
+
+        let current_trigger = header
+            .iter()
+            .position(|h| h == "trigger")
+            .and_then(|col| row.get(col))
+            .map(|s| s.trim())
+            .unwrap_or_default();
+        if current_trigger != expected_trigger {
+            return Err(format!(
+                "row {} has changed (expected trigger {:?}, found {:?})",
+                row_number, expected_trigger, current_trigger
+            )
+            .into());
+        }
+        fill_row(fields, &header, &mut row)?;
+
+        let range = format!("{}!A{}", SHEET_NAME, row_number);
+        self.client.update(&range, &[row]).await?;
+
+        self.refresh().await?;
+        Ok(())
+    }
+
+    /// ヘッダーより下の行を書き直し、残った古い行を消す
+    ///
+    /// 先に消してから書くと、書き込みに失敗したときにシートが空のまま残るので、書き込みを先に行う。
+    async fn replace_all(&self, entries: &[TriggerFields]) -> Result<(), BoxError> {
+        let header = self.header().await?;
+        let rows = entries
+            .iter()
+            .map(|fields| {
+                let mut row = vec![String::new(); header.len()];
+                fill_row(fields, &header, &mut row)?;
+                Ok(row)
+            })
+            .collect::<Result<Vec<_>, BoxError>>()?;
+
+        let first_row = HEADER_ROW + 1;
+        if !rows.is_empty() {
+            let range = format!("{}!A{}", SHEET_NAME, first_row);
+            self.client.update(&range, &rows).await?;
+        }
+        self.client
+            .clear(&format!("{}!A{}:ZZZ", SHEET_NAME, first_row + rows.len()))
+            .await?;
+
+        self.refresh().await?;
+        Ok(())
+    }
+}
+
+fn trim_header(header: Vec<String>) -> Vec<String> {
+    header.into_iter().map(|h| h.trim().to_string()).collect()
+}
+
+/// 1 行分のセルを項目に読み替える
+fn row_to_fields(header: &[String], row: &SheetRow) -> TriggerFields {
+    let get_field = |name: &str| row.get(name).trim().to_string();
+
+    TriggerFields {
+        trigger: get_field("trigger"),
+        aliases: alias_columns(header)
+            .into_iter()
+            .map(|col| get_field(&header[col]))
+            .filter(|alias| !alias.is_empty())
+            .collect(),
+        tags: get_field("tags")
+            .split([',', '、'])
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        title: get_field("title"),
+        description: get_field("description"),
+        response: get_field("response"),
+        thumbnail_url: get_field("right_small_image_URL"),
+        image_url: get_field("big_image_URL"),
+        color: get_field("color"),
+        url: get_field("url"),
+        footer: get_field("footer"),
+    }
+}
+
+/// ヘッダーに従って行の該当するセルを書き換える（該当する列がない項目は書き込まない）
+fn fill_row(
+    fields: &TriggerFields,
+    header: &[String],
+    row: &mut Vec<String>,
+) -> Result<(), BoxError> {
+    let Some(trigger_col) = header.iter().position(|h| h == "trigger") else {
+        return Err("trigger column was not found in the sheet header".into());
+    };
+    let alias_cols = alias_columns(header);
+    if fields.aliases.len() > alias_cols.len() {
+        return Err(format!(
+            "too many aliases: the sheet has only {} alias columns",
+            alias_cols.len()
+        )
+        .into());
+    }
+
+    if row.len() < header.len() {
+        row.resize(header.len(), String::new());
+    }
+    row[trigger_col] = fields.trigger.clone();
+    for (i, &col) in alias_cols.iter().enumerate() {
+        row[col] = fields.aliases.get(i).cloned().unwrap_or_default();
+    }
+    let tags = fields.tags.join(", ");
+    for (name, value) in [
+        ("tags", &tags),
+        ("title", &fields.title),
+        ("description", &fields.description),
+        ("response", &fields.response),
+        ("right_small_image_URL", &fields.thumbnail_url),
+        ("big_image_URL", &fields.image_url),
+        ("color", &fields.color),
+        ("url", &fields.url),
+        ("footer", &fields.footer),
+    ] {
+        if let Some(col) = header.iter().position(|h| h == name) {
+            row[col] = value.clone();
+        }
+    }
+    Ok(())
+}
+
+/// `aliasNN` 列の位置を番号順に返す
+fn alias_columns(header: &[String]) -> Vec<usize> {
+    let mut alias_cols: Vec<(u32, usize)> = header
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, h)| {
+            let number = h.strip_prefix("alias")?;
+            (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+                .then(|| (number.parse().unwrap_or(u32::MAX), idx))
+        })
+        .collect();
+    alias_cols.sort();
+    alias_cols.into_iter().map(|(_, idx)| idx).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_row_finds_columns_with_padded_header_names() {
+        let header = trim_header(vec![
+            "trigger ".to_string(),
+            " alias01".to_string(),
+            "title".to_string(),
+        ]);
+        let fields = TriggerFields {
+            trigger: "genkai".to_string(),
+            aliases: vec!["げんかい".to_string()],
+            title: "限界".to_string(),
+            ..Default::default()
+        };
+        let mut row = Vec::new();
+        fill_row(&fields, &header, &mut row).unwrap();
+
+        assert_eq!(row, ["genkai", "げんかい", "限界"]);
+    }
+}
@@ -0,0 +1,257 @@
+//! 天気予報を取得できる地点（気象庁の一次細分区域の代表地点）の一覧
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::normalize::normalize_keyword;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 地点の一覧（livedoor 天気互換の XML）の URL
+pub const DEFAULT_PRIMARY_AREA_URL: &str = "https://weather.tsukumijima.net/primary_area.xml";
+
+/// 一覧を取得できなかったときに使う主な地点
+const BUNDLED_CITIES: &[(&str, &str, &str)] = &[
+    ("016010", "札幌", "北海道"),
+    ("040010", "仙台", "宮城県"),
+    ("060010", "山形", "山形県"),
+    ("130010", "東京", "東京都"),
+    ("140010", "横浜", "神奈川県"),
+    ("230010", "名古屋", "愛知県"),
+    ("260010", "京都", "京都府"),
+    ("270000", "大阪", "大阪府"),
+    ("340010", "広島", "広島県"),
+    ("400010", "福岡", "福岡県"),
+    ("471010", "那覇", "沖縄県"),
+];
+
+/// 取得に失敗してから取り直すまでの最初の間隔（失敗が続くたびに倍にする）
+const MIN_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// 取り直すまでの間隔の上限
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 天気予報の地点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct City {
+    /// 「130010」のような 6 桁の地点コード
+    pub id: String,
+    pub name: String,
+    pub prefecture: String,
+}
+
+impl City {
+    /// 入力補完などに表示する名前（「東京（東京都）」）
+    pub fn label(&self) -> String {
+        format!("{}（{}）", self.name, self.prefecture)
+    }
+}
+
+/// 地点の一覧を取得してメモリ上にキャッシュする
+///
+/// 一覧はほとんど変わらないので、一度取得できたら取り直さない。
+/// 取得に失敗したときは主な地点だけの一覧を返し、間隔を空けて取り直す。
+pub struct CityDirectory {
+    http: reqwest::Client,
+    url: String,
+    cache: Mutex<Cache>,
+}
+
+enum Cache {
+    Empty,
+    Fetched(Arc<Vec<City>>),
+    /// 取得に失敗したので、`retry_at` までは主な地点だけの一覧を使う
+    Fallback {
+        cities: Arc<Vec<City>>,
+        retry_at: Instant,
+        retry_interval: Duration,
+    },
+}
+
+impl CityDirectory {
+    pub fn new() -> Self {
+        Self {
+            http: super::http_client().clone(),
+            url: DEFAULT_PRIMARY_AREA_URL.to_string(),
+            cache: Mutex::new(Cache::Empty),
+        }
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// すべての地点を返す
+    pub async fn cities(&self) -> Arc<Vec<City>> {
+        let fallback = {
+            let mut cache = self.cache.lock().unwrap();
+            let retry_interval = match &*cache {
+                Cache::Fetched(cities) => return cities.clone(),
+                Cache::Fallback {
+                    cities, retry_at, ..
+                } if Instant::now() < *retry_at => return cities.clone(),
+                Cache::Fallback { retry_interval, .. } => {
+                    (*retry_interval * 2).min(MAX_RETRY_INTERVAL)
+                }
+                Cache::Empty => MIN_RETRY_INTERVAL,
+            };
+            // 取得している間に来た呼び出しは待たせずに主な地点だけの一覧を返す
+            let fallback = Arc::new(bundled_cities());
+            *cache = Cache::Fallback {
+                cities: fallback.clone(),
+                retry_at: Instant::now() + retry_interval,
+                retry_interval,
+            };
+            fallback
+        };
+
+        match self.fetch().await {
+            Ok(fetched) if !fetched.is_empty() => {
+                let fetched = Arc::new(fetched);
+                *self.cache.lock().unwrap() = Cache::Fetched(fetched.clone());
+                fetched
+            }
+            Ok(_) => {
+                println!("Primary area list was empty - URL: {}", self.url);
+                fallback
+            }
+            Err(e) => {
+                println!(
+                    "Failed to fetch primary area list - URL: {}, Error: {}",
+                    self.url, e
+                );
+                fallback
+            }
+        }
+    }
+
+    /// 地点コードか地点名（「東京」や「東京（東京都）」）で地点を探す
+    pub async fn find(&self, query: &str) -> Option<City> {
+        let query = query.trim();
+        let normalized = normalize_keyword(query);
+        self.cities()
+            .await
+            .iter()
+            .find(|city| {
+                city.id == query
+                    || normalize_keyword(&city.name) == normalized
+                    || normalize_keyword(&city.label()) == normalized
+            })
+            .cloned()
+    }
+
+    /// 地点名か都道府県名に入力を含む地点を最大 `limit` 件返す（地点名の前方一致を優先）
+    pub async fn search(&self, query: &str, limit: usize) -> Vec<City> {
+        let query = normalize_keyword(query);
+        let cities = self.cities().await;
+        let mut matches: Vec<(u8, &City)> = cities
+            .iter()
+            .filter_map(|city| {
+                let name = normalize_keyword(&city.name);
+                let rank = if name.starts_with(&query) {
+                    0
+                } else if name.contains(&query) {
+                    1
+                } else if normalize_keyword(&city.prefecture).contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, city))
+            })
+            .collect();
+        // 同じ順位のものは一覧の順（北から南）のままにする
+        matches.sort_by_key(|&(rank, _)| rank);
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, city)| city.clone())
+            .collect()
+    }
+
+    async fn fetch(&self) -> Result<Vec<City>, BoxError> {
+        let xml = self
+            .http
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Ok(parse_primary_area(&xml))
+    }
+}
+
+impl Default for CityDirectory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `<pref title="…">` の中の `<city title="…" id="…">` を順に読み取る
+fn parse_primary_area(xml: &str) -> Vec<City> {
+    let tag = Regex::new(r"<(pref|city)\s([^>]*)>").unwrap();
+    let attribute = Regex::new(r#"(\w+)="([^"]*)""#).unwrap();
+
+    let mut prefecture = String::new();
+    let mut cities = Vec::new();
+    for captures in tag.captures_iter(xml) {
+        let attributes = &captures[2];
+        let get = |name: &str| {
+            attribute
+                .captures_iter(attributes)
+                .find(|a| &a[1] == name)
+                .map(|a| a[2].to_string())
+        };
+        match &captures[1] {
+            "pref" => prefecture = get("title").unwrap_or_default(),
+            _ => {
+                if let (Some(name), Some(id)) = (get("title"), get("id")) {
+                    cities.push(City {
+                        id,
+                        name,
+                        prefecture: prefecture.clone(),
+                    });
+                }
+            }
+        }
+    }
+    cities
+}
+
+fn bundled_cities() -> Vec<City> {
+    BUNDLED_CITIES
+        .iter()
+        .map(|&(id, name, prefecture)| City {
+            id: id.to_string(),
+            name: name.to_string(),
+            prefecture: prefecture.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_fetch_falls_back_and_is_not_retried_at_once() {
+        // 接続できないポートなのですぐに失敗する
+        let directory = CityDirectory::new().with_url("http://127.0.0.1:9/primary_area.xml");
+
+        let first = directory.cities().await;
+        assert_eq!(first.len(), BUNDLED_CITIES.len());
+        // 再試行の時刻まではキャッシュした一覧をそのまま返す
+        assert!(Arc::ptr_eq(&first, &directory.cities().await));
+    }
+
+    #[test]
+    fn parses_prefectures_and_cities() {
+        let xml = r#"<pref title="東京都"><city title="東京" id="130010" source="x"></city><city title="大島" id="130020"></city></pref>"#;
+        let cities = parse_primary_area(xml);
+        assert_eq!(cities.len(), 2);
+        assert_eq!(cities[1].label(), "大島（東京都）");
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use crate::json_store::JsonFileStore;
+
+/// ユーザーごとの既定の地点コードを JSON ファイルに保存する（`/weather` で地点を省略したときに使う）
+pub struct DefaultCityStore {
+    file: JsonFileStore<HashMap<u64, String>>,
+}
+
+impl DefaultCityStore {
+    /// 空の状態で作成する（設定されるまでファイルには触れない）
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: JsonFileStore::new(path),
+        }
+    }
+
+    /// ファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            file: JsonFileStore::load(path)?,
+        })
+    }
+
+    pub fn get(&self, user_id: u64) -> Option<String> {
+        self.file.read(|cities| cities.get(&user_id).cloned())
+    }
+
+    /// 既定の地点を設定して保存する
+    pub async fn set(&self, user_id: u64, city_id: &str) -> io::Result<()> {
+        self.file
+            .update(|cities| {
+                cities.insert(user_id, city_id.to_string());
+            })
+            .await
+    }
+}
@@ -0,0 +1,420 @@
+//! 気象庁の防災情報 JSON（週間天気予報・警報・注意報）
+
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 気象庁の防災情報 JSON のベース URL
+pub const DEFAULT_JMA_BASE_URL: &str = "https://www.jma.go.jp/bosai";
+
+/// 週間天気予報（府県予報区ごと）
+#[derive(Debug, Clone)]
+pub struct WeeklyForecast {
+    pub publishing_office: String,
+    /// 発表時刻（ISO 8601 形式）
+    pub report_datetime: String,
+    /// 一次細分区域ごとの予報
+    pub areas: Vec<WeeklyArea>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WeeklyArea {
+    /// 「東京地方」のような区域の名前
+    pub name: String,
+    /// 「130010」のような区域のコード
+    pub code: String,
+    /// 気温を観測する地点の名前（「東京」など）
+    pub temperature_point: Option<String>,
+    pub days: Vec<WeeklyDay>,
+}
+
+/// 1 日分の週間天気予報
+#[derive(Debug, Clone)]
+pub struct WeeklyDay {
+    /// 予報の対象日時（ISO 8601 形式）
+    pub time: String,
+    /// 気象庁の天気コード（「100」など）
+    pub weather_code: String,
+    /// 降水確率（%）
+    pub pop: Option<u8>,
+    /// 予報の信頼度（A, B, C）
+    pub reliability: Option<String>,
+    pub temp_min: Option<f64>,
+    pub temp_max: Option<f64>,
+}
+
+impl WeeklyDay {
+    /// 天気コードに対応する天気
+    pub fn weather(&self) -> &'static str {
+        weather_code_label(&self.weather_code)
+    }
+}
+
+/// 府県予報区で発表中の警報・注意報
+#[derive(Debug, Clone)]
+pub struct Warnings {
+    /// 発表時刻（ISO 8601 形式）
+    pub report_datetime: String,
+    /// 見出し（「東京都では、…に注意してください。」など）
+    pub headline: String,
+    /// 一次細分区域ごとの警報・注意報
+    pub areas: Vec<AreaWarnings>,
+}
+
+impl Warnings {
+    pub fn area(&self, code: &str) -> Option<&AreaWarnings> {
+        self.areas.iter().find(|area| area.code == code)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AreaWarnings {
+    /// 一次細分区域のコード（「130010」など）
+    pub code: String,
+    pub warnings: Vec<Warning>,
+}
+
+impl AreaWarnings {
+    /// 発表中（解除されていない）の警報・注意報
+    pub fn active(&self) -> impl Iterator<Item = &Warning> {
+        self.warnings.iter().filter(|w| w.is_active())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Warning {
+    /// 気象庁の警報・注意報コード（「03」= 大雨警報など）
+    pub code: String,
+    /// 「発表」「継続」「解除」など
+    pub status: String,
+}
+
+impl Warning {
+    pub fn name(&self) -> &'static str {
+        warning_name(&self.code)
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.code.is_empty() && self.status != "解除"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ForecastReport {
+    #[serde(default)]
+    publishing_office: String,
+    #[serde(default)]
+    report_datetime: String,
+    #[serde(default)]
+    time_series: Vec<TimeSeries>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimeSeries {
+    #[serde(default)]
+    time_defines: Vec<String>,
+    #[serde(default)]
+    areas: Vec<SeriesArea>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SeriesArea {
+    area: AreaName,
+    weather_codes: Vec<String>,
+    pops: Vec<String>,
+    reliabilities: Vec<String>,
+    temps_min: Vec<String>,
+    temps_max: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AreaName {
+    name: String,
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WarningReport {
+    #[serde(default)]
+    report_datetime: String,
+    #[serde(default)]
+    headline_text: String,
+    #[serde(default)]
+    area_types: Vec<WarningAreaType>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WarningAreaType {
+    #[serde(default)]
+    areas: Vec<WarningArea>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WarningArea {
+    code: String,
+    #[serde(default)]
+    warnings: Vec<WarningItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WarningItem {
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    status: String,
+}
+
+/// 府県予報区の週間天気予報を取得する（`office_code` は「130000」のような府県予報区のコード）
+pub async fn fetch_weekly_forecast(office_code: &str) -> Result<WeeklyForecast, BoxError> {
+    let url = format!(
+        "{}/forecast/data/forecast/{}.json",
+        DEFAULT_JMA_BASE_URL, office_code
+    );
+    // 1 つ目が今日から明後日まで、2 つ目が週間天気予報
+    let reports: Vec<ForecastReport> = get_json(&url).await?;
+    let Some(report) = reports.into_iter().nth(1) else {
+        return Err(format!("weekly forecast not found for office {}", office_code).into());
+    };
+
+    let mut series = report.time_series.into_iter();
+    let weather = series
+        .next()
+        .ok_or("weekly forecast has no weather series")?;
+    let temperature = series.next();
+
+    // 天気の区域と気温の観測地点は同じ順に並んでいる
+    let areas = weather
+        .areas
+        .into_iter()
+        .enumerate()
+        .map(|(i, area)| {
+            let temps = temperature.as_ref().and_then(|t| t.areas.get(i));
+            let days = weather
+                .time_defines
+                .iter()
+                .enumerate()
+                .map(|(day, time)| {
+                    // 気温は日付が一致する位置の値を使う（今日の分はないことがある）
+                    let temp_index = temperature.as_ref().and_then(|t| {
+                        t.time_defines
+                            .iter()
+                            .position(|d| d.get(..10) == time.get(..10))
+                    });
+                    let temp = |values: fn(&SeriesArea) -> &Vec<String>| {
+                        temps
+                            .zip(temp_index)
+                            .and_then(|(area, index)| values(area).get(index))
+                            .and_then(|v| v.trim().parse().ok())
+                    };
+                    WeeklyDay {
+                        time: time.clone(),
+                        weather_code: area.weather_codes.get(day).cloned().unwrap_or_default(),
+                        pop: area.pops.get(day).and_then(|p| p.trim().parse().ok()),
+                        reliability: area
+                            .reliabilities
+                            .get(day)
+                            .filter(|r| !r.trim().is_empty())
+                            .cloned(),
+                        temp_min: temp(|a| &a.temps_min),
+                        temp_max: temp(|a| &a.temps_max),
+                    }
+                })
+                .collect();
+            WeeklyArea {
+                name: area.area.name,
+                code: area.area.code,
+                temperature_point: temps.map(|t| t.area.name.clone()),
+                days,
+            }
+        })
+        .collect();
+
+    Ok(WeeklyForecast {
+        publishing_office: report.publishing_office,
+        report_datetime: report.report_datetime,
+        areas,
+    })
+}
+
+/// 府県予報区の警報・注意報を取得する（`office_code` は「130000」のような府県予報区のコード）
+pub async fn fetch_warnings(office_code: &str) -> Result<Warnings, BoxError> {
+    let url = format!(
+        "{}/warning/data/warning/{}.json",
+        DEFAULT_JMA_BASE_URL, office_code
+    );
+    let report: WarningReport = get_json(&url).await?;
+
+    // 1 つ目が一次細分区域、2 つ目が市町村ごとの警報・注意報
+    let areas = report
+        .area_types
+        .into_iter()
+        .next()
+        .map(|area_type| area_type.areas)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|area| AreaWarnings {
+            code: area.code,
+            warnings: area
+                .warnings
+                .into_iter()
+                .map(|w| Warning {
+                    code: w.code,
+                    status: w.status,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(Warnings {
+        report_datetime: report.report_datetime,
+        headline: report.headline_text,
+        areas,
+    })
+}
+
+async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, BoxError> {
+    let response = match super::http_client().get(url).send().await {
+        Ok(resp) => resp,
+        Err(e) => {
+            println!("JMA HTTP request failed - URL: {}, Error: {}", url, e);
+            return Err(Box::new(e));
+        }
+    };
+    Ok(response.error_for_status()?.json().await?)
+}
+
+/// 警報・注意報コードに対応する名前
+pub fn warning_name(code: &str) -> &'static str {
+    match code {
+        "02" => "暴風雪警報",
+        "03" => "大雨警報",
+        "04" => "洪水警報",
+        "05" => "暴風警報",
+        "06" => "大雪警報",
+        "07" => "波浪警報",
+        "08" => "高潮警報",
+        "10" => "大雨注意報",
+        "12" => "大雪注意報",
+        "13" => "風雪注意報",
+        "14" => "雷注意報",
+        "15" => "強風注意報",
+        "16" => "波浪注意報",
+        "17" => "融雪注意報",
+        "18" => "洪水注意報",
+        "19" => "高潮注意報",
+        "20" => "濃霧注意報",
+        "21" => "乾燥注意報",
+        "22" => "なだれ注意報",
+        "23" => "低温注意報",
+        "24" => "霜注意報",
+        "25" => "着氷注意報",
+        "26" => "着雪注意報",
+        "27" => "その他の注意報",
+        "32" => "暴風雪特別警報",
+        "33" => "大雨特別警報",
+        "35" => "暴風特別警報",
+        "36" => "大雪特別警報",
+        "37" => "波浪特別警報",
+        "38" => "高潮特別警報",
+        _ => "不明な警報・注意報",
+    }
+}
+
+/// 警報・注意報コードの重さ（特別警報が 2、警報が 1、注意報が 0）
+pub fn warning_level(code: &str) -> u8 {
+    let name = warning_name(code);
+    if name.ends_with("特別警報") {
+        2
+    } else if name.ends_with("警報") {
+        1
+    } else {
+        0
+    }
+}
+
+/// 天気コードに対応する天気（主なもの以外は大まかな天気を返す）
+pub fn weather_code_label(code: &str) -> &'static str {
+    match code {
+        "100" => "晴れ",
+        "101" => "晴時々曇",
+        "102" => "晴一時雨",
+        "103" => "晴時々雨",
+        "104" => "晴一時雪",
+        "110" | "111" => "晴後曇",
+        "112" | "113" => "晴後雨",
+        "115" => "晴後雪",
+        "200" => "曇り",
+        "201" => "曇時々晴",
+        "202" => "曇一時雨",
+        "203" => "曇時々雨",
+        "204" => "曇一時雪",
+        "210" | "211" => "曇後晴",
+        "212" | "213" => "曇後雨",
+        "214" => "曇後雪",
+        "300" => "雨",
+        "301" => "雨時々晴",
+        "302" => "雨時々止む",
+        "303" => "雨時々雪",
+        "308" => "暴風雨",
+        "311" => "雨後晴",
+        "313" => "雨後曇",
+        "314" => "雨後雪",
+        "400" => "雪",
+        "401" => "雪時々晴",
+        "402" => "雪時々止む",
+        "403" => "雪時々雨",
+        "406" | "407" => "暴風雪",
+        "411" => "雪後晴",
+        "413" => "雪後曇",
+        "414" => "雪後雨",
+        _ => match code.chars().next() {
+            Some('1') => "晴れ",
+            Some('2') => "曇り",
+            Some('3') => "雨",
+            Some('4') => "雪",
+            _ => "不明",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_level_ranks_special_warnings_highest() {
+        assert_eq!(warning_level("33"), 2); // 大雨特別警報
+        assert_eq!(warning_level("03"), 1); // 大雨警報
+        assert_eq!(warning_level("10"), 0); // 大雨注意報
+        assert_eq!(warning_level("99"), 0);
+    }
+
+    #[test]
+    fn lifted_warnings_are_not_active() {
+        let area = AreaWarnings {
+            code: "130010".to_string(),
+            warnings: vec![
+                Warning {
+                    code: "03".to_string(),
+                    status: "継続".to_string(),
+                },
+                Warning {
+                    code: "10".to_string(),
+                    status: "解除".to_string(),
+                },
+                Warning {
+                    code: String::new(),
+                    status: "発表警報・注意報はなし".to_string(),
+                },
+            ],
+        };
+        let active: Vec<&str> = area.active().map(|w| w.code.as_str()).collect();
+        assert_eq!(active, ["03"]);
+    }
+}
@@ -0,0 +1,234 @@
+pub mod area;
+pub mod defaults;
+pub mod jma;
+pub mod render;
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+
+pub use area::{City, CityDirectory};
+pub use defaults::DefaultCityStore;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 天気関係の API へのリクエスト 1 回あたりの制限時間
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 天気関係の API で共有する HTTP クライアント（応答がないときに待ち続けないよう制限時間を付ける）
+pub(crate) fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new())
+    })
+}
+
+/// 天気予報 API（気象庁の予報を JSON にしたもの）のベース URL
+pub const DEFAULT_FORECAST_BASE_URL: &str = "https://weather.tsukumijima.net/api/forecast/city";
+
+/// 1 地点分の天気予報（今日・明日・明後日）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherForecast {
+    /// 予報の発表時刻（ISO 8601 形式）
+    pub public_time: String,
+    /// 予報を発表した気象台（「気象庁」「横浜地方気象台」など）
+    pub publishing_office: String,
+    /// 「東京都 東京 の天気」のような見出し
+    pub title: String,
+    /// 気象庁の予報ページの URL
+    pub link: String,
+    pub description: Description,
+    /// 今日・明日・明後日の予報（発表時刻によっては明後日がない）
+    pub forecasts: Vec<DailyForecast>,
+    pub location: Location,
+}
+
+impl WeatherForecast {
+    /// 今日の予報
+    pub fn today(&self) -> Option<&DailyForecast> {
+        self.forecasts.first()
+    }
+}
+
+/// 天気概況
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Description {
+    pub public_time: String,
+    /// 見出し（ないことが多い）
+    pub headline_text: String,
+    /// 本文
+    pub body_text: String,
+    /// 見出しと本文を合わせたもの
+    pub text: String,
+}
+
+/// 1 日分の予報
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyForecast {
+    /// `YYYY-MM-DD` 形式の日付
+    pub date: String,
+    /// 「今日」「明日」「明後日」
+    pub date_label: String,
+    /// 「晴れ」「曇時々雨」のような短い天気
+    pub telop: String,
+    #[serde(default)]
+    pub detail: ForecastDetail,
+    #[serde(default)]
+    pub temperature: Temperatures,
+    #[serde(default)]
+    pub chance_of_rain: ChanceOfRain,
+    pub image: Option<WeatherImage>,
+}
+
+impl DailyForecast {
+    pub fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()
+    }
+
+    /// 詳しい天気（全角スペースを除いたもの）。なければ `telop` を返す
+    pub fn weather(&self) -> String {
+        self.detail
+            .weather
+            .as_ref()
+            .map(|w| w.replace('　', ""))
+            .unwrap_or_else(|| self.telop.clone())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ForecastDetail {
+    /// 「晴れ　夜　くもり」のような詳しい天気
+    pub weather: Option<String>,
+    /// 風
+    pub wind: Option<String>,
+    /// 波（海に面していない地域ではない）
+    pub wave: Option<String>,
+}
+
+/// 最低・最高気温（発表時刻によっては今日の最低気温がない）
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Temperatures {
+    pub min: Temperature,
+    pub max: Temperature,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Temperature {
+    #[serde(deserialize_with = "number_string")]
+    pub celsius: Option<f64>,
+    #[serde(deserialize_with = "number_string")]
+    pub fahrenheit: Option<f64>,
+}
+
+/// 6 時間ごとの降水確率（%。発表時刻を過ぎた時間帯などはない）
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct ChanceOfRain {
+    #[serde(rename = "T00_06", deserialize_with = "percent")]
+    pub midnight: Option<u8>,
+    #[serde(rename = "T06_12", deserialize_with = "percent")]
+    pub morning: Option<u8>,
+    #[serde(rename = "T12_18", deserialize_with = "percent")]
+    pub afternoon: Option<u8>,
+    #[serde(rename = "T18_24", deserialize_with = "percent")]
+    pub night: Option<u8>,
+}
+
+impl ChanceOfRain {
+    /// 1 日のうちで最も高い降水確率
+    pub fn max(&self) -> Option<u8> {
+        [self.midnight, self.morning, self.afternoon, self.night]
+            .into_iter()
+            .flatten()
+            .max()
+    }
+}
+
+/// 気象庁の天気アイコン
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeatherImage {
+    pub title: String,
+    /// SVG の URL
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Location {
+    /// 地方（「関東」など）
+    pub area: String,
+    pub prefecture: String,
+    /// 一次細分区域（「東部」など）
+    pub district: String,
+    pub city: String,
+}
+
+/// 「12」のような数値の文字列を読む（`null` や数値でないものは `None`）
+fn number_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.and_then(|v| v.trim().parse().ok()))
+}
+
+/// 「30%」のような文字列を読む（「--%」は `None`）
+fn percent<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.and_then(|v| v.trim().trim_end_matches('%').parse().ok()))
+}
+
+/// 地点の天気予報を取得する（`citycode` は「130010」のような 6 桁の地点コード）
+pub async fn fetch_forecast(citycode: &str) -> Result<WeatherForecast, BoxError> {
+    let url = format!("{}/{}", DEFAULT_FORECAST_BASE_URL, citycode);
+
+    let response = match http_client().get(&url).send().await {
+        Ok(resp) => resp,
+        Err(e) => {
+            println!(
+                "Weather API HTTP request failed - URL: {}, Error: {}",
+                url, e
+            );
+            return Err(Box::new(e));
+        }
+    };
+
+    // エラーの本文を予報として読まないよう、成功以外のステータスはここでエラーにする
+    let response = match response.error_for_status() {
+        Ok(resp) => resp,
+        Err(e) => {
+            println!(
+                "Weather API returned non-success status - URL: {}, Error: {}",
+                url, e
+            );
+            return Err(Box::new(e));
+        }
+    };
+
+    let response_text = match response.text().await {
+        Ok(text) => text,
+        Err(e) => {
+            println!("Failed to get response text - Error: {}", e);
+            return Err(Box::new(e));
+        }
+    };
+
+    match serde_json::from_str(&response_text) {
+        Ok(forecast) => Ok(forecast),
+        Err(e) => {
+            println!("Failed to parse weather API JSON response - Error: {}", e);
+            println!("Response text: {}", response_text);
+            Err(Box::new(e))
+        }
+    }
+}
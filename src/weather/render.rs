@@ -0,0 +1,137 @@
+//! `WeatherForecast` を Discord に投稿する Markdown にする
+
+use super::jma::{WeeklyArea, WeeklyForecast};
+use super::{DailyForecast, WeatherForecast};
+
+/// 今日の天気・最高気温・降水確率を 1 項目にまとめる（朝の定期投稿用）
+pub fn summary(forecast: &WeatherForecast) -> String {
+    match forecast.today() {
+        Some(today) => day_summary(&forecast.location.city, today),
+        None => "天気情報を取得できませんでした".to_string(),
+    }
+}
+
+/// 1 日分の天気・最高気温・降水確率を `name` の項目としてまとめる
+pub fn day_summary(name: &str, day: &DailyForecast) -> String {
+    let rain = &day.chance_of_rain;
+    format!(
+        "- {}: {}\n  - 🌡️ 最高気温: {} ℃\n  - ☔ 朝: {} | 昼: {} | 晩: {}",
+        name,
+        day.weather(),
+        celsius(day.temperature.max.celsius),
+        percent(rain.morning),
+        percent(rain.afternoon),
+        percent(rain.night)
+    )
+}
+
+/// 発表されているすべての日の予報と天気概況をまとめる
+pub fn details(forecast: &WeatherForecast) -> String {
+    let mut lines = vec![format!("**{}**", forecast.title)];
+    for day in &forecast.forecasts {
+        lines.push(day_details(day));
+    }
+    if !forecast.description.body_text.is_empty() {
+        lines.push(format!(
+            "> {}",
+            forecast.description.body_text.trim().replace('\n', "\n> ")
+        ));
+    }
+    lines.push(format!("({} 発表)", forecast.publishing_office));
+    lines.join("\n")
+}
+
+/// 1 日分の天気・気温・6 時間ごとの降水確率・風をまとめる
+pub fn day_details(day: &DailyForecast) -> String {
+    let rain = &day.chance_of_rain;
+    let mut text = format!(
+        "- {}: {}\n  - 🌡️ 最低: {} ℃ | 最高: {} ℃\n  - ☔ 0-6時: {} | 6-12時: {} | 12-18時: {} | 18-24時: {}",
+        day.date_label,
+        day.weather(),
+        celsius(day.temperature.min.celsius),
+        celsius(day.temperature.max.celsius),
+        percent(rain.midnight),
+        percent(rain.morning),
+        percent(rain.afternoon),
+        percent(rain.night)
+    );
+    if let Some(wind) = &day.detail.wind {
+        text.push_str(&format!("\n  - 🌬️ {}", wind.replace('　', " ")));
+    }
+    text
+}
+
+/// 週間天気予報を区域ごとにまとめる
+pub fn weekly(forecast: &WeeklyForecast) -> String {
+    let mut lines: Vec<String> = forecast.areas.iter().map(weekly_area).collect();
+    lines.push(format!("({} 発表)", forecast.publishing_office));
+    lines.join("\n")
+}
+
+/// 1 区域分の週間天気予報（1 日 1 行）
+pub fn weekly_area(area: &WeeklyArea) -> String {
+    let mut lines = vec![match &area.temperature_point {
+        Some(point) => format!("**{}**（気温は{}）", area.name, point),
+        None => format!("**{}**", area.name),
+    }];
+    for day in &area.days {
+        let date = chrono::DateTime::parse_from_rfc3339(&day.time)
+            .map(|time| time.format("%-m/%-d").to_string())
+            .unwrap_or_else(|_| day.time.clone());
+        let weather = day.weather();
+        lines.push(format!(
+            "- {} {} {} 🌡️ {} / {} ℃ ☔ {}",
+            date,
+            emoji(weather),
+            weather,
+            celsius(day.temp_min),
+            celsius(day.temp_max),
+            percent(day.pop)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// 埋め込みのフィールドに表示する 1 日分の天気・気温・降水確率
+pub fn day_field(day: &DailyForecast) -> String {
+    format!(
+        "{} {}\n🌡️ {} / {} ℃\n☔ {}",
+        emoji(&day.telop),
+        day.telop,
+        celsius(day.temperature.min.celsius),
+        celsius(day.temperature.max.celsius),
+        percent(day.chance_of_rain.max())
+    )
+}
+
+/// 天気（「晴時々曇」など）に合う絵文字
+pub fn emoji(telop: &str) -> &'static str {
+    let later = |c: char| telop.chars().skip(1).any(|t| t == c);
+    if telop.contains('雷') {
+        return "⛈️";
+    }
+    match telop.chars().next() {
+        Some('晴') if later('雨') => "🌦️",
+        Some('晴') if later('雪') => "🌨️",
+        Some('晴') if later('曇') => "⛅",
+        Some('晴') => "☀️",
+        Some('曇') if later('雨') => "🌧️",
+        Some('曇') if later('雪') => "🌨️",
+        Some('曇') => "☁️",
+        Some('雨') if later('雪') => "🌨️",
+        Some('雨') => "☔",
+        Some('雪') => "❄️",
+        Some('暴') => "🌀",
+        _ => "🌡️",
+    }
+}
+
+/// 気温（発表されていなければ「--」）
+pub fn celsius(value: Option<f64>) -> String {
+    value.map_or_else(|| "--".to_string(), |v| v.to_string())
+}
+
+/// 降水確率（発表されていなければ「--%」）
+pub fn percent(value: Option<u8>) -> String {
+    value.map_or_else(|| "--%".to_string(), |v| format!("{}%", v))
+}